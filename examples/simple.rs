@@ -4,7 +4,7 @@ fn main() {
     println!("query: {:#?}", query);
     println!(
         "sql query: {}",
-        query.unwrap().into_sql_statement(None).unwrap().to_string()
+        query.unwrap().into_sql_statement(None).unwrap()
     );
 
     let filter = "age=lt.42&(student=eq.true|gender=eq.'M')&group_by=sum(age),grade,gender&having=min(age)=gt.42&order_by=age.desc,height.asc&page=20&page_size=100";
@@ -0,0 +1,88 @@
+//! The compact bracket syntax for filters, `[age<13][student=true]`, which
+//! is rewritten into the dotted form restq parses, `age=lt.13&student=eq.true`
+
+use crate::scan::scan;
+use restq::Error;
+use std::cmp::Reverse;
+
+/// the infix operators and their dotted name, the two character operators
+/// come first so `<=` is not read as `<`
+const OPERATORS: &[(&str, &str)] = &[
+    (">=", "gte"),
+    ("<=", "lte"),
+    ("!=", "neq"),
+    ("<", "lt"),
+    (">", "gt"),
+    ("=", "eq"),
+];
+
+/// Rewrite each bracket group into its dotted condition, joined with AND.
+///
+/// A group is a single condition, the connectors and the parenthesis which
+/// would join it with others are rejected unless they are quoted.
+pub(crate) fn to_dotted(input: &str) -> Result<String, Error> {
+    let invalid = |reason: &str| {
+        Error::GenericError(format!(
+            "Invalid bracket filter: `{}`, {}",
+            input, reason
+        ))
+    };
+    let mut conditions = vec![];
+    let mut rest = input.trim();
+    while !rest.is_empty() {
+        if !rest.starts_with('[') {
+            return Err(invalid("expecting a `[`"));
+        }
+        let end = closing_bracket(rest)
+            .ok_or_else(|| invalid("missing a closing `]`"))?;
+        let group = &rest[1..end];
+        if scan(group)
+            .any(|scanned| !scanned.quoted && "&|()".contains(scanned.ch))
+        {
+            return Err(invalid(
+                "a group is a single condition, its `&`, `|`, `(` or `)` \
+                 have to be quoted",
+            ));
+        }
+        conditions.push(to_condition(group).ok_or_else(|| {
+            invalid("expecting a condition such as `[age<13]`")
+        })?);
+        rest = rest[end + 1..].trim_start();
+    }
+    if conditions.is_empty() {
+        return Err(invalid("expecting at least one condition"));
+    }
+    Ok(conditions.join("&"))
+}
+
+/// the position of the `]` closing the group, skipping quoted strings
+fn closing_bracket(input: &str) -> Option<usize> {
+    scan(input)
+        .skip(1)
+        .find(|scanned| scanned.is(']'))
+        .map(|scanned| scanned.index)
+}
+
+/// `age<13` into `age=lt.13`, the operator is the first one found. The
+/// value can't start with another operator, ie: `age<>13`, which would
+/// otherwise be read as the string `>13`.
+fn to_condition(group: &str) -> Option<String> {
+    let (position, symbol, name) = OPERATORS
+        .iter()
+        .filter_map(|(symbol, name)| {
+            group.find(symbol).map(|position| (position, symbol, name))
+        })
+        // the leftmost operator, and the longest one at that position
+        .min_by_key(|(position, symbol, _)| {
+            (*position, Reverse(symbol.len()))
+        })?;
+    let left = group[..position].trim();
+    let right = group[position + symbol.len()..].trim();
+    if left.is_empty()
+        || right.is_empty()
+        || right.starts_with(|ch| "<>=!".contains(ch))
+    {
+        return None;
+    }
+    Some(format!("{}={}.{}", left, name, right))
+}
@@ -0,0 +1,538 @@
+//! Operations on a parsed `Expr` which restq doesn't provide
+use restq::ast::{
+    BinaryOperation,
+    Expr,
+    Function,
+    Operator,
+    Value,
+};
+use std::fmt;
+
+/// Visit the expression and all of its sub-expressions, parent first
+/// and then the operands from left to right
+pub fn walk<'a, F>(expr: &'a Expr, f: &mut F)
+where
+    F: FnMut(&'a Expr),
+{
+    f(expr);
+    match expr {
+        Expr::Column(_) | Expr::Value(_) => (),
+        Expr::Function(function) => {
+            for param in function.params.iter() {
+                walk(param, f);
+            }
+        }
+        Expr::BinaryOperation(binop) => {
+            walk(&binop.left, f);
+            walk(&binop.right, f);
+        }
+        Expr::Nested(expr) => walk(expr, f),
+    }
+}
+
+/// Visit the expression and all of its sub-expressions, parent first
+pub fn walk_mut<F>(expr: &mut Expr, f: &mut F)
+where
+    F: FnMut(&mut Expr),
+{
+    f(expr);
+    match expr {
+        Expr::Column(_) | Expr::Value(_) => (),
+        Expr::Function(function) => {
+            for param in function.params.iter_mut() {
+                walk_mut(param, f);
+            }
+        }
+        Expr::BinaryOperation(binop) => {
+            walk_mut(&mut binop.left, f);
+            walk_mut(&mut binop.right, f);
+        }
+        Expr::Nested(expr) => walk_mut(expr, f),
+    }
+}
+
+/// The literal values in this expression which are supplied as parameters
+/// of a prepared statement, from left to right.
+///
+/// The `null` in an `is`/`is_not` check is not a parameter, since it
+/// becomes part of the sql syntax as in `deleted_at IS NOT NULL`
+pub fn bind_values(expr: &Expr) -> Vec<&Value> {
+    let mut values = vec![];
+    collect_bind_values(expr, &mut values);
+    values
+}
+
+fn collect_bind_values<'a>(expr: &'a Expr, values: &mut Vec<&'a Value>) {
+    match expr {
+        Expr::Column(_) => (),
+        Expr::Value(value) => values.push(value),
+        Expr::Function(function) => {
+            for param in function.params.iter() {
+                collect_bind_values(param, values);
+            }
+        }
+        Expr::BinaryOperation(binop) => {
+            collect_bind_values(&binop.left, values);
+            if !is_null_check(binop) {
+                collect_bind_values(&binop.right, values);
+            }
+        }
+        Expr::Nested(expr) => collect_bind_values(expr, values),
+    }
+}
+
+/// `deleted_at=is.null` or `deleted_at=is_not.null`
+pub fn is_null_check(binop: &BinaryOperation) -> bool {
+    (binop.operator == Operator::Is || binop.operator == Operator::IsNot)
+        && binop.right == Expr::Value(Value::Null)
+}
+
+/// Apply `f` in place to every literal value in this expression
+pub fn map_values<F>(expr: &mut Expr, f: &mut F)
+where
+    F: FnMut(&mut Value),
+{
+    walk_mut(expr, &mut |expr| {
+        if let Expr::Value(value) = expr {
+            f(value);
+        }
+    })
+}
+
+/// Lowercase the names of all the functions used in this expression,
+/// since sql function names are case-insensitive, `SUM(age)` and `sum(age)`
+/// will then be equal.
+pub fn normalize_function_names(expr: &mut Expr) {
+    walk_mut(expr, &mut |expr| {
+        if let Expr::Function(function) = expr {
+            function.name = function.name.to_lowercase();
+        }
+    })
+}
+
+/// Whether the operator compares its operands, as opposed to joining
+/// conditions (`&`, `|`) or doing arithmetic
+pub fn is_comparison(operator: &Operator) -> bool {
+    match operator {
+        Operator::Eq
+        | Operator::Neq
+        | Operator::Lt
+        | Operator::Lte
+        | Operator::Gt
+        | Operator::Gte
+        | Operator::Like
+        | Operator::In
+        | Operator::NotIn
+        | Operator::Is
+        | Operator::IsNot
+        | Operator::Ilike
+        | Operator::Starts => true,
+        Operator::And
+        | Operator::Or
+        | Operator::Plus
+        | Operator::Minus
+        | Operator::Multiply
+        | Operator::Divide
+        | Operator::Modulus => false,
+    }
+}
+
+/// The expressions joined by AND at the top level of this filter,
+/// looking through the grouping parenthesis
+pub fn conjuncts(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Nested(expr) => conjuncts(expr),
+        Expr::BinaryOperation(binop) if binop.operator == Operator::And => {
+            let mut exprs = conjuncts(&binop.left);
+            exprs.extend(conjuncts(&binop.right));
+            exprs
+        }
+        _ => vec![expr],
+    }
+}
+
+/// The number of conditions in this expression
+pub fn condition_count(expr: &Expr) -> usize {
+    let mut count = 0;
+    walk(expr, &mut |expr| {
+        if let Expr::BinaryOperation(binop) = expr {
+            if is_comparison(&binop.operator) {
+                count += 1;
+            }
+        }
+    });
+    count
+}
+
+/// Evaluate the arithmetic on number literals, ie: `2+3` becomes `5`, while
+/// expressions involving a column such as `price*2` are left as is.
+/// A division or modulus by zero is not folded.
+pub fn fold_constants(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Function(function) => {
+            Expr::Function(Function {
+                name: function.name.clone(),
+                params: function.params.iter().map(fold_constants).collect(),
+            })
+        }
+        Expr::Nested(nested) => {
+            match fold_constants(nested) {
+                Expr::Value(value) => Expr::Value(value),
+                folded => Expr::Nested(Box::new(folded)),
+            }
+        }
+        Expr::BinaryOperation(binop) => {
+            let left = fold_constants(&binop.left);
+            let right = fold_constants(&binop.right);
+            if let (
+                Expr::Value(Value::Number(l)),
+                Expr::Value(Value::Number(r)),
+            ) = (&left, &right)
+            {
+                let folded = match binop.operator {
+                    Operator::Plus => Some(l + r),
+                    Operator::Minus => Some(l - r),
+                    Operator::Multiply => Some(l * r),
+                    Operator::Divide if *r != 0.0 => Some(l / r),
+                    Operator::Modulus if *r != 0.0 => Some(l % r),
+                    _ => None,
+                };
+                if let Some(folded) = folded {
+                    return Expr::Value(Value::Number(folded));
+                }
+            }
+            Expr::BinaryOperation(Box::new(BinaryOperation {
+                left,
+                operator: binop.operator.clone(),
+                right,
+            }))
+        }
+        Expr::Column(_) | Expr::Value(_) => expr.clone(),
+    }
+}
+
+/// Whether the right side of the operator is a list of values, as for
+/// `in` and `not_in`, which SQL generation and validation branch on
+pub fn requires_list(operator: &Operator) -> bool {
+    *operator == Operator::In || *operator == Operator::NotIn
+}
+
+/// Whether this is the `*` of `count(*)`, which restq reads as the string
+/// `'*'` since it is neither a column nor a value.
+///
+/// A `*` between two operands, as in `price*2`, is a multiplication. A `*`
+/// on its own is only valid as a function argument, where it is this
+/// wildcard. A quoted `'*'` argument can't be told apart from it, and is
+/// also taken as the wildcard.
+pub fn is_wildcard(expr: &Expr) -> bool {
+    match expr {
+        Expr::Value(Value::String(v)) => v == "*",
+        _ => false,
+    }
+}
+
+/// The conditions of a filter, from left to right, which can be modified
+/// in place, ie: to rewrite a tenant column.
+///
+/// Only the `&` and `|` connectors and the grouping parenthesis are
+/// descended into, the operands of a condition are not.
+pub fn conditions_mut(
+    expr: &mut Expr,
+) -> impl Iterator<Item = &mut BinaryOperation> {
+    let mut conditions = vec![];
+    collect_conditions_mut(expr, &mut conditions);
+    conditions.into_iter()
+}
+
+fn collect_conditions_mut<'a>(
+    expr: &'a mut Expr,
+    conditions: &mut Vec<&'a mut BinaryOperation>,
+) {
+    match expr {
+        Expr::Nested(expr) => collect_conditions_mut(expr, conditions),
+        Expr::BinaryOperation(binop) => {
+            if is_comparison(&binop.operator) {
+                conditions.push(binop.as_mut());
+            } else if binop.operator == Operator::And
+                || binop.operator == Operator::Or
+            {
+                let BinaryOperation { left, right, .. } = binop.as_mut();
+                collect_conditions_mut(left, conditions);
+                collect_conditions_mut(right, conditions);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// The operator to use when the operands of a binary operation are swapped,
+/// such that `13<age` can become `age>13`.
+///
+/// Returns `None` for an operator that has no mirrored form, ie: `like`, `in`
+pub fn mirror(operator: &Operator) -> Option<Operator> {
+    match operator {
+        Operator::Lt => Some(Operator::Gt),
+        Operator::Lte => Some(Operator::Gte),
+        Operator::Gt => Some(Operator::Lt),
+        Operator::Gte => Some(Operator::Lte),
+        Operator::Eq
+        | Operator::Neq
+        | Operator::And
+        | Operator::Or
+        | Operator::Plus
+        | Operator::Multiply => Some(operator.clone()),
+        Operator::Minus
+        | Operator::Divide
+        | Operator::Modulus
+        | Operator::Like
+        | Operator::In
+        | Operator::NotIn
+        | Operator::Is
+        | Operator::IsNot
+        | Operator::Ilike
+        | Operator::Starts => None,
+    }
+}
+
+/// Exchange the left and right operands, adjusting the operator so that
+/// the operation still means the same thing.
+///
+/// Returns false and leaves the operation untouched when the operator
+/// can not be mirrored.
+pub fn swap_operands(binop: &mut BinaryOperation) -> bool {
+    match mirror(&binop.operator) {
+        Some(operator) => {
+            binop.operator = operator;
+            std::mem::swap(&mut binop.left, &mut binop.right);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Convert a filter into its conjunctive normal form: an AND of OR groups,
+/// by distributing OR over AND, ie: `a|(b&c)` becomes `(a|b)&(a|c)`.
+///
+/// Grouping parenthesis are not significant in the result, each OR group
+/// that is joined with other groups is nested in a new parenthesis.
+/// Negation is not part of the grammar, so there is no De Morgan's rewrite.
+///
+/// The number of OR groups grows exponentially with the OR'ed ANDs, ie:
+/// `(a&b)|(c&d)|(e&f)` has 8 of them, so this returns `None` instead of
+/// expanding to more than `max_clauses` OR groups.
+pub fn to_cnf(expr: &Expr, max_clauses: usize) -> Option<Expr> {
+    let clauses: Vec<Expr> = cnf_clauses(expr, max_clauses)?
+        .into_iter()
+        .map(|clause| join(clause, Operator::Or))
+        .collect();
+    let is_grouped = clauses.len() > 1;
+    let clauses = clauses
+        .into_iter()
+        .map(|clause| {
+            match &clause {
+                Expr::BinaryOperation(binop)
+                    if is_grouped && binop.operator == Operator::Or =>
+                {
+                    Expr::Nested(Box::new(clause))
+                }
+                _ => clause,
+            }
+        })
+        .collect();
+    Some(join(clauses, Operator::And))
+}
+
+/// the AND of OR clauses of this expression, or `None` when there are
+/// more than `max_clauses` of them
+fn cnf_clauses(expr: &Expr, max_clauses: usize) -> Option<Vec<Vec<Expr>>> {
+    let clauses = match expr {
+        Expr::Nested(expr) => cnf_clauses(expr, max_clauses)?,
+        Expr::BinaryOperation(binop) if binop.operator == Operator::And => {
+            let mut clauses = cnf_clauses(&binop.left, max_clauses)?;
+            clauses.extend(cnf_clauses(&binop.right, max_clauses)?);
+            clauses
+        }
+        Expr::BinaryOperation(binop) if binop.operator == Operator::Or => {
+            let left = cnf_clauses(&binop.left, max_clauses)?;
+            let right = cnf_clauses(&binop.right, max_clauses)?;
+            if left.len().saturating_mul(right.len()) > max_clauses {
+                return None;
+            }
+            let mut clauses = vec![];
+            for l in left.iter() {
+                for r in right.iter() {
+                    clauses.push(l.iter().chain(r.iter()).cloned().collect());
+                }
+            }
+            clauses
+        }
+        _ => vec![vec![expr.clone()]],
+    };
+    if clauses.len() > max_clauses {
+        None
+    } else {
+        Some(clauses)
+    }
+}
+
+/// join the expressions with the connector, associating to the right
+/// the same way the parser does
+fn join(mut exprs: Vec<Expr>, connector: Operator) -> Expr {
+    let last = exprs.pop().expect("must have at least one expression");
+    exprs.into_iter().rev().fold(last, |right, left| {
+        Expr::BinaryOperation(Box::new(BinaryOperation {
+            left,
+            operator: connector.clone(),
+            right,
+        }))
+    })
+}
+
+/// Read the values compared to these columns as a boolean when they are
+/// `1`/`0`, `yes`/`no` or `on`/`off`, which some clients send for the
+/// boolean columns. The words are parsed as column names by restq, and the
+/// numbers as numbers, since the grammar doesn't know the column types.
+pub fn coerce_booleans(expr: &mut Expr, columns: &[String]) {
+    for condition in conditions_mut(expr) {
+        let is_boolean = match &condition.left {
+            Expr::Column(column) => columns.contains(&column.name),
+            _ => false,
+        };
+        if is_boolean {
+            if let Some(value) = lenient_bool(&condition.right) {
+                condition.right = Expr::Value(Value::Bool(value));
+            }
+        }
+    }
+}
+
+fn lenient_bool(expr: &Expr) -> Option<bool> {
+    let word = match expr {
+        Expr::Value(Value::Number(number)) if *number == 1.0 => {
+            return Some(true)
+        }
+        Expr::Value(Value::Number(number)) if *number == 0.0 => {
+            return Some(false)
+        }
+        Expr::Value(Value::String(word)) => word,
+        Expr::Column(column) => &column.name,
+        _ => return None,
+    };
+    match word.to_lowercase().as_str() {
+        "yes" | "on" => Some(true),
+        "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Turn the conditions marked by `keywords::mark_is_operator` back into
+/// the `is` condition they were written as, for `null`, `true` and `false`.
+/// A marked column which isn't the value of a condition gets its name back.
+///
+/// restq's `into_sql_statement` has no conversion for the `is` operator and
+/// panics on it, `sql::to_sql` has to be used for these conditions.
+pub(crate) fn restore_is_operator(expr: &mut Expr, marker: &str) {
+    let is_value = |column: &str| {
+        match column.strip_prefix(marker)? {
+            "is.null" => Some(Value::Null),
+            "is.true" => Some(Value::Bool(true)),
+            "is.false" => Some(Value::Bool(false)),
+            _ => None,
+        }
+    };
+    for condition in conditions_mut(expr) {
+        if condition.operator != Operator::Eq {
+            continue;
+        }
+        let value = match &condition.right {
+            Expr::Column(column) => is_value(&column.name),
+            _ => None,
+        };
+        if let Some(value) = value {
+            condition.operator = Operator::Is;
+            condition.right = Expr::Value(value);
+        }
+    }
+    walk_mut(expr, &mut |expr| {
+        if let Expr::Column(column) = expr {
+            if is_value(&column.name).is_some() {
+                column.name = column.name[marker.len()..].to_string();
+            }
+        }
+    });
+}
+
+/// Remove the argument marked by `keywords::mark_no_arguments`, so `now()`
+/// is a function without parameters rather than `now('')`
+pub(crate) fn restore_no_arguments(expr: &mut Expr, marker: &str) {
+    walk_mut(expr, &mut |expr| {
+        if let Expr::Function(function) = expr {
+            if let [Expr::Column(column)] = function.params.as_slice() {
+                if column.name == marker {
+                    function.params.clear();
+                }
+            }
+        }
+    });
+}
+
+/// Display an expression in the infix form, `age < 13 AND gender = 'M'`,
+/// for humans, rather than the dotted form of its own `Display`,
+/// `age=lt.13&gender=eq.'M'`.
+///
+/// The connectors are grouped in a parenthesis as they were parsed.
+pub struct Infix<'a>(pub &'a Expr);
+
+impl fmt::Display for Infix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Expr::Column(column) => write!(f, "{}", column.name),
+            Expr::Value(Value::String(v)) => {
+                write!(f, "'{}'", v.replace('\'', "''"))
+            }
+            Expr::Value(value) => write!(f, "{}", value),
+            Expr::Function(function) => {
+                write!(f, "{}(", function.name)?;
+                for (i, param) in function.params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Infix(param))?;
+                }
+                write!(f, ")")
+            }
+            Expr::Nested(expr) => write!(f, "({})", Infix(expr)),
+            Expr::BinaryOperation(binop) => {
+                let is_connector = binop.operator == Operator::And
+                    || binop.operator == Operator::Or;
+                let grouped = |expr: &Expr| {
+                    match expr {
+                        Expr::BinaryOperation(side) if is_connector => {
+                            (side.operator == Operator::And
+                                || side.operator == Operator::Or)
+                                && side.operator != binop.operator
+                        }
+                        Expr::BinaryOperation(_) => true,
+                        _ => false,
+                    }
+                };
+                for (i, side) in [&binop.left, &binop.right].iter().enumerate()
+                {
+                    if i > 0 {
+                        write!(
+                            f,
+                            " {} ",
+                            crate::sql::sql_operator(&binop.operator)
+                        )?;
+                    }
+                    if grouped(side) {
+                        write!(f, "({})", Infix(side))?;
+                    } else {
+                        write!(f, "{}", Infix(side))?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Non-binding hints on how a parsed `Select` may perform, derived purely
+//! from its structure, without any knowledge of the schema or the indexes
+use crate::expr;
+use restq::ast::{
+    Expr,
+    Operator,
+    Select,
+    Value,
+};
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum Hint {
+    /// a `like` or `ilike` pattern starting with a wildcard, which a btree
+    /// index on the column can't be used for
+    LeadingWildcard { column: String },
+    /// there is no `limit` or `page`, so every matching row is fetched
+    Unbounded,
+    /// the conditions of an OR group are on different columns, which may
+    /// prevent the use of a single index
+    OrAcrossColumns { columns: Vec<String> },
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hint::LeadingWildcard { column } => {
+                write!(
+                    f,
+                    "leading-wildcard like on `{}` can't use a btree index",
+                    column
+                )
+            }
+            Hint::Unbounded => write!(f, "unbounded query, no limit present"),
+            Hint::OrAcrossColumns { columns } => {
+                write!(
+                    f,
+                    "OR across columns `{}` may prevent index use",
+                    columns.join("`, `")
+                )
+            }
+        }
+    }
+}
+
+/// Derive the hints for this query, in the order of the clauses they
+/// are found in
+pub fn explain_plan_hints(select: &Select) -> Vec<Hint> {
+    let mut hints = vec![];
+    for clause in select.filter.iter().chain(select.having.iter()) {
+        leading_wildcard_hints(clause, &mut hints);
+        or_hints(clause, &mut hints);
+    }
+    if select.range.is_none() {
+        hints.push(Hint::Unbounded);
+    }
+    hints
+}
+
+fn leading_wildcard_hints(expr: &Expr, hints: &mut Vec<Hint>) {
+    expr::walk(expr, &mut |expr| {
+        if let Expr::BinaryOperation(binop) = expr {
+            let is_like = binop.operator == Operator::Like
+                || binop.operator == Operator::Ilike;
+            if let (true, Expr::Column(column), Expr::Value(Value::String(v))) =
+                (is_like, &binop.left, &binop.right)
+            {
+                if v.starts_with('%') || v.starts_with('*') {
+                    hints.push(Hint::LeadingWildcard {
+                        column: column.name.clone(),
+                    });
+                }
+            }
+        }
+    })
+}
+
+fn or_hints(expr: &Expr, hints: &mut Vec<Hint>) {
+    match expr {
+        Expr::Nested(expr) => or_hints(expr, hints),
+        Expr::BinaryOperation(binop) if binop.operator == Operator::And => {
+            or_hints(&binop.left, hints);
+            or_hints(&binop.right, hints);
+        }
+        Expr::BinaryOperation(binop) if binop.operator == Operator::Or => {
+            let disjuncts = disjuncts(expr);
+            let mut columns: Vec<String> = vec![];
+            for disjunct in disjuncts.iter() {
+                expr::walk(disjunct, &mut |expr| {
+                    if let Expr::Column(column) = expr {
+                        if !columns.contains(&column.name) {
+                            columns.push(column.name.clone());
+                        }
+                    }
+                });
+            }
+            if columns.len() > 1 {
+                hints.push(Hint::OrAcrossColumns { columns });
+            }
+            // an OR group may still be nested in one of the disjuncts
+            for disjunct in disjuncts {
+                or_hints(disjunct, hints);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// the expressions joined by OR at the top of this expression
+fn disjuncts(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Nested(nested) => {
+            match &**nested {
+                Expr::BinaryOperation(binop)
+                    if binop.operator == Operator::Or =>
+                {
+                    disjuncts(nested)
+                }
+                _ => vec![expr],
+            }
+        }
+        Expr::BinaryOperation(binop) if binop.operator == Operator::Or => {
+            let mut exprs = disjuncts(&binop.left);
+            exprs.extend(disjuncts(&binop.right));
+            exprs
+        }
+        _ => vec![expr],
+    }
+}
@@ -0,0 +1,162 @@
+//! The keywords of a query are matched ignoring their case, ie: `AGE=EQ.13`,
+//! `order_by=age.DESC` or `student=eq.TRUE`, by lowercasing them before the
+//! input is handed to restq, which only matches the lowercase keywords.
+//! The column names and the string values keep their case.
+//!
+//! The syntax restq parses as something else, `is.null` and the `()` of a
+//! function without arguments, is marked here before parsing, for the parsed
+//! expression to be fixed up.
+use crate::scan::{
+    scan,
+    VALUE_END,
+};
+
+/// the operators of a condition, which come between the `=` and the `.`
+pub(crate) const OPERATORS: &[&str] = &[
+    "eq", "neq", "lt", "lte", "gt", "gte", "in", "not_in", "is", "is_not",
+    "like", "ilike", "starts",
+];
+
+/// the values which are keywords, right after the operator
+const VALUES: &[&str] = &["true", "false", "null"];
+
+/// the directions of an `order_by` entry, after its `.`
+const DIRECTIONS: &[&str] = &["asc", "desc"];
+
+/// Lowercase the operators, the `true`, `false` and `null` values right
+/// after an operator and the directions in the `order_by` parameter
+pub(crate) fn lowercase(input: &str) -> String {
+    let words = words(input);
+    let mut output = String::with_capacity(input.len());
+    let mut last = 0;
+    let mut after_operator = None;
+    for (start, end, param) in words {
+        let word = &input[start..end];
+        let before = input[..start].chars().last();
+        let next = input[end..].chars().next();
+        let ends_value = match next {
+            None => true,
+            Some(next) => VALUE_END.contains(next),
+        };
+        let lower = word.to_ascii_lowercase();
+        let is_keyword = if before == Some('=') && next == Some('.') {
+            OPERATORS.contains(&lower.as_str())
+        } else if before == Some('.') && ends_value {
+            (after_operator == Some(start - 1)
+                && VALUES.contains(&lower.as_str()))
+                || (param == "order_by" && DIRECTIONS.contains(&lower.as_str()))
+        } else {
+            false
+        };
+        after_operator = if is_keyword && next == Some('.') {
+            Some(end)
+        } else {
+            None
+        };
+        if is_keyword && lower != word {
+            output.push_str(&input[last..start]);
+            output.push_str(&lower);
+            last = end;
+        }
+    }
+    output.push_str(&input[last..]);
+    output
+}
+
+/// A column name which is not in the input, for `mark_is_operator` and
+/// `mark_no_arguments`
+pub(crate) fn marker(input: &str) -> String {
+    let mut marker = "_m_".to_string();
+    while input.contains(&marker) {
+        marker.insert(0, '_');
+    }
+    marker
+}
+
+/// restq has no `is` operator, it reads `deleted_at=is.null` as `deleted_at`
+/// equal to the column `is.null`, the same as `deleted_at=eq.is.null`. Mark
+/// the conditions written with `=is.` by turning their `is.null`, `is.true`
+/// or `is.false` into the column `{marker}is.null`, which can't be confused
+/// with a column of the input, for `expr::restore_is_operator`. The marker
+/// is only inserted, for the positions in the rewritten input to be mapped
+/// back to the input.
+pub(crate) fn mark_is_operator(input: &str, marker: &str) -> String {
+    let words = words(input);
+    let mut output = String::with_capacity(input.len());
+    let mut last = 0;
+    for pair in words.windows(2) {
+        let ((start, end, _), (value_start, value_end, _)) = (pair[0], pair[1]);
+        let ends_value = match input[value_end..].chars().next() {
+            None => true,
+            Some(next) => VALUE_END.contains(next),
+        };
+        if &input[start..end] == "is"
+            && input[..start].ends_with('=')
+            && value_start == end + 1
+            && input[end..].starts_with('.')
+            && ends_value
+            && VALUES.contains(&&input[value_start..value_end])
+        {
+            output.push_str(&input[last..start]);
+            output.push_str(marker);
+            last = start;
+        }
+    }
+    output.push_str(&input[last..]);
+    output
+}
+
+/// restq parses the `()` of a function without arguments, ie: `now()`, as
+/// a single empty string argument, the same as `now('')`. Mark it by putting
+/// the column `marker` as the argument, for `expr::restore_no_arguments`.
+pub(crate) fn mark_no_arguments(input: &str, marker: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut previous: Option<char> = None;
+    let mut scanned = scan(input).peekable();
+    while let Some(current) = scanned.next() {
+        output.push(current.ch);
+        let is_call = previous
+            .map(|ch| ch.is_alphanumeric() || ch == '_')
+            .unwrap_or(false);
+        if current.is('(') && is_call {
+            if let Some(next) = scanned.peek() {
+                if next.is(')') {
+                    output.push_str(marker);
+                }
+            }
+        }
+        previous = Some(current.ch);
+    }
+    output
+}
+
+/// The byte range of each unquoted word, together with the key of the
+/// parameter it is in, ie: `order_by` for the words of `order_by=age.desc`
+fn words(input: &str) -> Vec<(usize, usize, &str)> {
+    let mut words = vec![];
+    let mut start: Option<usize> = None;
+    let mut param = "";
+    let mut param_start = 0;
+    for scanned in scan(input) {
+        let (i, ch) = (scanned.index, scanned.ch);
+        let is_word = !scanned.quoted && (ch.is_alphanumeric() || ch == '_');
+        if let (Some(s), false) = (start, is_word) {
+            words.push((s, i, param));
+            if scanned.is('=') && scanned.depth == 0 && param_start == s {
+                param = &input[s..i];
+            }
+            start = None;
+        }
+        if (scanned.is('&') || scanned.is('?')) && scanned.depth == 0 {
+            param = "";
+            param_start = i + 1;
+        }
+        if is_word && start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, input.len(), param));
+    }
+    words
+}
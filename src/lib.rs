@@ -161,7 +161,7 @@
 //! ```
 //! Which translate to the sql statement:
 //! ```sql
-//! SELECT * FROM person WHERE age < 42 AND (student = true OR gender = 'M') GROUP BY sum(age), grade, gender HAVING min(age) > 42 ORDER BY age DESC, height ASC LIMIT 100 OFFSET 1900 ROWS
+//! SELECT * FROM person WHERE age < 42 AND (student = true OR gender = 'M') GROUP BY sum(age), grade, gender HAVING min(age) > 42 ORDER BY age DESC, height ASC LIMIT 100 OFFSET 1900
 //! ```
 //! Note: However, you don't want to convert to the sql statement directly to avoid sql injection
 //! attack. You need to validate the tables and columns if it is allowed to be accessed by the
@@ -173,11 +173,43 @@
 pub use restq;
 
 pub use restq::{
-    ast::{Expr, Select},
+    ast::{
+        BinaryOperation,
+        Column,
+        Direction,
+        Expr,
+        Function,
+        Operator,
+        Order,
+        Select,
+        Value,
+    },
     parser::filter_expr,
-    to_chars, Error,
+    to_chars,
+    Error,
 };
 
+pub use options::{
+    EmptyValue,
+    ParseOptions,
+};
+use params::Params;
+pub use parse_error::ParseError;
+
+mod bracket;
+pub mod expr;
+pub mod hint;
+mod keywords;
+mod options;
+mod params;
+mod parse_error;
+pub mod postgrest;
+mod scan;
+pub mod select;
+pub mod sql;
+pub mod validate;
+pub mod value;
+
 /// Parse a path and query in a url to a Select AST
 /// Example:
 /// ```rust
@@ -191,9 +223,82 @@ pub use restq::{
 ///         query.unwrap().into_sql_statement(None).unwrap().to_string()
 ///     );
 /// ```
-pub fn parse_query(input: &str) -> Result<Select, Error> {
-    let input_chars = to_chars(input);
-    restq::parse_select_chars(&input_chars)
+pub fn parse_query(input: &str) -> Result<Select, ParseError> {
+    parse_query_with(input, &ParseOptions::default())
+}
+
+/// Parse a path and query in a url to a Select AST, using the supplied options
+///
+/// An empty or whitespace-only input yields the default, empty `Select`
+///
+/// `first=10` fetches the first 10 rows, the same as `limit=10&offset=0`.
+/// `last=10` fetches the last 10 rows by reversing the `order_by`, which is
+/// required, so the fetched rows come in the reversed order.
+///
+/// `dir=desc` sets the direction of the `order_by` entries which don't have
+/// their own, ie: `order_by=name,age.asc&dir=desc` is `name.desc,age.asc`.
+///
+/// `deleted_at=is.null` is an `is` condition, as are `is.true` and
+/// `is.false`. restq's `into_sql_statement` panics on the `is` operator, so
+/// a query with these conditions is turned into sql with `sql::to_sql`.
+pub fn parse_query_with(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Select, ParseError> {
+    if input.trim().is_empty() {
+        return Ok(Select::default());
+    }
+    let mut params = Params::split(input, options.separator);
+    for param in params.params.iter_mut() {
+        param.text = value::quote_literals(&keywords::lowercase(&param.text));
+    }
+    let rewrite = options.apply(&mut params)?;
+    let marker = keywords::marker(input);
+    params.path = keywords::mark_no_arguments(&params.path, &marker);
+    for param in params.params.iter_mut() {
+        param.text = keywords::mark_no_arguments(
+            &keywords::mark_is_operator(&param.text, &marker),
+            &marker,
+        );
+    }
+    let url = params.to_url();
+    check_nesting_depth(&url)?;
+    let input_chars = to_chars(&url);
+    let mut select =
+        restq::parse_select_chars(&input_chars).map_err(|error| {
+            ParseError::from(error)
+                .map_position(|position| params.input_position(position))
+        })?;
+    if let (Some(direction), Some(order_by)) =
+        (rewrite.default_direction, select.order_by.as_mut())
+    {
+        for order in order_by.iter_mut() {
+            if order.direction.is_none() {
+                order.direction = Some(direction.clone());
+            }
+        }
+    }
+    if rewrite.reverse_order {
+        if select.order_by.is_none() {
+            return Err(Error::GenericError(
+                "last requires an order_by to know which rows are last"
+                    .to_string(),
+            )
+            .into());
+        }
+        select::reverse_order_by(&mut select);
+    }
+    for clause in select.filter.iter_mut().chain(select.having.iter_mut()) {
+        expr::restore_is_operator(clause, &marker);
+    }
+    select::for_each_expr_mut(&mut select, |expr| {
+        expr::restore_no_arguments(expr, &marker)
+    });
+    if let Some(filter) = select.filter.as_mut() {
+        expr::coerce_booleans(filter, &options.lenient_bool_columns);
+    }
+    validate::validate_finite_numbers(&select).map_err(invalid_number)?;
+    Ok(select)
 }
 
 /// Parse the query in a url to an Expression
@@ -206,11 +311,299 @@ pub fn parse_query(input: &str) -> Result<Select, Error> {
 ///     let result = parse_filter(filter);
 ///     println!("filter_only: {:#?}", result);
 /// ```
-pub fn parse_filter(input: &str) -> Result<Expr, Error> {
+pub fn parse_filter(input: &str) -> Result<Expr, ParseError> {
+    parse_filter_input(input, false)
+}
+
+/// parse the filter, which has to span the whole input when `to_end`,
+/// otherwise the first filter of the input is parsed
+fn parse_filter_input(input: &str, to_end: bool) -> Result<Expr, ParseError> {
+    let marker = keywords::marker(input);
+    let rewritten = value::quote_literals(&keywords::lowercase(input));
+    let rewritten = keywords::mark_no_arguments(
+        &keywords::mark_is_operator(&rewritten, &marker),
+        &marker,
+    );
+    parse_filter_str(&rewritten, &marker, to_end).map_err(|error| {
+        ParseError::from(error).map_position(|position| {
+            Some(parse_error::align(input, &rewritten, position))
+        })
+    })
+}
+
+/// Parse a filter written in the compact bracket syntax, where each
+/// condition is in its own bracket with an infix operator, `<`, `<=`, `>`,
+/// `>=`, `=` or `!=`, and the conditions are joined with AND.
+///
+/// Each condition has to be complete, anything after its value is an error,
+/// as is a second operator such as the `>` of `[age<>1]`. The errors have
+/// no position, since each condition is rewritten into the dotted form
+/// before it is parsed.
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let filter = parse_bracket_filter("[age<13][student=true]").unwrap();
+///     assert_eq!(filter, parse_filter("age=lt.13&student=eq.true").unwrap());
+/// ```
+pub fn parse_bracket_filter(input: &str) -> Result<Expr, ParseError> {
+    parse_filter_input(&bracket::to_dotted(input)?, true)
+        .map_err(|error| error.map_position(|_| None))
+}
+
+/// Parse a query from its path and its raw query string, which most web
+/// frameworks provide separately. The query string may start with the `?`.
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let query = parse_params("/person", "age=lt.42&order_by=age.desc&page=2").unwrap();
+///     assert_eq!(query, parse_query("/person?age=lt.42&order_by=age.desc&page=2").unwrap());
+///     assert_eq!(query.to_string(), "person?age=lt.42&order_by=age.desc&page=2&page_size=20");
+///
+///     let query = parse_params("/person", "").unwrap();
+///     assert_eq!(query.to_string(), "person");
+/// ```
+pub fn parse_params(path: &str, query: &str) -> Result<Select, ParseError> {
+    let query = query.trim_start_matches('?');
+    if query.is_empty() {
+        parse_query(path)
+    } else {
+        parse_query(&format!("{}?{}", path, query))
+    }
+}
+
+/// Parse a query from its path and the already split key/value pairs of
+/// its query string, as provided by most web frameworks. The order of the
+/// pairs is kept, and the values of repeated `group_by` and `order_by`
+/// keys are joined.
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let pairs = [("age", "lt.42"), ("order_by", "age.desc"), ("order_by", "height.asc")];
+///     let query = parse_pairs("/person", &pairs).unwrap();
+///     assert_eq!(query.to_string(), "person?age=lt.42&order_by=age.desc,height.asc");
+/// ```
+pub fn parse_pairs(
+    path: &str,
+    pairs: &[(&str, &str)],
+) -> Result<Select, ParseError> {
+    let mut params: Vec<(&str, String)> = vec![];
+    for (key, value) in pairs {
+        let is_list = *key == "group_by" || *key == "order_by";
+        match params.iter_mut().find(|(k, _)| is_list && k == key) {
+            Some((_, values)) => {
+                values.push(',');
+                values.push_str(value);
+            }
+            None => params.push((key, value.to_string())),
+        }
+    }
+    let params = Params::new(
+        path,
+        params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect(),
+    );
+    parse_query(&params.to_url())
+}
+
+/// Parse a single value, as it appears on the right side of a condition,
+/// using the same precedence as restq: `null`, then `true`/`false`, then a
+/// number, then a quoted string. An unquoted word is a string.
+///
+/// The whole input must be the value.
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     assert_eq!(parse_value("-4.5").unwrap(), Value::Number(-4.5));
+///     assert_eq!(parse_value("'M'").unwrap(), Value::String("M".to_string()));
+///     assert!(parse_value("42&age").is_err());
+///     assert!(parse_value("1e400").is_err());
+/// ```
+pub fn parse_value(input: &str) -> Result<Value, ParseError> {
+    let invalid = || {
+        ParseError::from(Error::GenericError(format!(
+            "Invalid value: `{}`",
+            input
+        )))
+    };
+    let value = match parse_operand(input).map_err(|_| invalid())? {
+        Expr::Value(value) => value,
+        Expr::Column(column) => Value::String(column.name),
+        _ => return Err(invalid()),
+    };
+    validate::validate_finite_value(&value).map_err(invalid_number)?;
+    Ok(value)
+}
+
+/// restq reads a number too large for an `f64`, ie: `1e400`, as an infinity
+fn invalid_number(error: validate::ValidationError) -> Error {
+    Error::GenericError(error.to_string())
+}
+
+/// Parse a single function call, such as `coalesce(a, b, c)`, the
+/// whitespace between the arguments is ignored.
+///
+/// The whole input must be the function call.
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let function = parse_function("min(age)").unwrap();
+///     assert_eq!(function.name, "min");
+///     assert_eq!(function.params.len(), 1);
+/// ```
+pub fn parse_function(input: &str) -> Result<Function, ParseError> {
+    let invalid = || {
+        ParseError::from(Error::GenericError(format!(
+            "Invalid function: `{}`",
+            input
+        )))
+    };
+    let input = strip_unquoted_whitespace(input);
+    check_nesting_depth(&input)?;
+    match parse_operand(&input).map_err(|_| invalid())? {
+        Expr::Function(function) => Ok(function),
+        _ => Err(invalid()),
+    }
+}
+
+/// restq's parsers for the operands of a condition are private, so the
+/// operand is parsed as the right side of a condition instead, up to the
+/// end of the input
+fn parse_operand(input: &str) -> Result<Expr, Error> {
+    let marker = keywords::marker(input);
+    let condition = value::quote_literals(&format!("_=eq.{}", input));
+    let input_chars =
+        to_chars(&keywords::mark_no_arguments(&condition, &marker));
+    let filter =
+        (filter_expr() - restq::pom::parser::end()).parse(&input_chars)?;
+    match filter {
+        Expr::BinaryOperation(binop) if binop.operator == Operator::Eq => {
+            let mut operand = binop.right;
+            expr::restore_no_arguments(&mut operand, &marker);
+            Ok(operand)
+        }
+        _ => {
+            Err(Error::GenericError(format!(
+                "Expecting a single operand: `{}`",
+                input
+            )))
+        }
+    }
+}
+
+/// remove the whitespace which is not inside a quoted string
+fn strip_unquoted_whitespace(input: &str) -> String {
+    scan::scan(input)
+        .filter(|scanned| scanned.quoted || !scanned.ch.is_whitespace())
+        .map(|scanned| scanned.ch)
+        .collect()
+}
+
+fn parse_filter_str(
+    input: &str,
+    marker: &str,
+    to_end: bool,
+) -> Result<Expr, Error> {
+    check_nesting_depth(input)?;
     let input_chars = to_chars(input);
-    parse_filter_chars(&input_chars)
+    let mut filter = if to_end {
+        (filter_expr() - restq::pom::parser::end()).parse(&input_chars)?
+    } else {
+        filter_expr().parse(&input_chars)?
+    };
+    expr::restore_is_operator(&mut filter, marker);
+    expr::restore_no_arguments(&mut filter, marker);
+    validate::validate_finite_expr(&filter).map_err(invalid_number)?;
+    Ok(filter)
+}
+
+/// The maximum depth of nested parenthesis or braces allowed in the input.
+/// Grouped filters and function calls, such as `f(f(f(...)))`, are parsed
+/// recursively, so an excessively nested input could overflow the stack.
+pub const MAX_NESTING_DEPTH: usize = 64;
+
+/// reject the input early, before it reaches the recursive parser,
+/// when the nesting of `(` and `{` exceeds `MAX_NESTING_DEPTH`. The
+/// brackets inside quoted strings are not nested groups and don't count.
+fn check_nesting_depth(input: &str) -> Result<(), Error> {
+    let mut depth = 0usize;
+    for (position, scanned) in scan::scan(input).enumerate() {
+        match scanned.ch {
+            _ if scanned.quoted => (),
+            '(' | '{' => {
+                depth += 1;
+                if depth > MAX_NESTING_DEPTH {
+                    return Err(Error::GenericError(format!(
+                        "Nesting exceeds the maximum depth of {} at position {}",
+                        MAX_NESTING_DEPTH, position
+                    )));
+                }
+            }
+            ')' | '}' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Parse a sort direction as it appears in `order_by`, the inverse of the
+/// `Display` of `Direction`, ignoring the case
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let direction = parse_direction("desc").unwrap();
+///     assert_eq!(direction, Direction::Desc);
+///     assert_eq!(direction.to_string(), "desc");
+/// ```
+pub fn parse_direction(input: &str) -> Result<Direction, ParseError> {
+    match input.to_ascii_lowercase().as_str() {
+        "asc" => Ok(Direction::Asc),
+        "desc" => Ok(Direction::Desc),
+        _ => {
+            Err(Error::GenericError(format!(
+                "Invalid direction: `{}`, expecting asc or desc",
+                input
+            ))
+            .into())
+        }
+    }
 }
 
-fn parse_filter_chars(input: &[char]) -> Result<Expr, Error> {
-    Ok(filter_expr().parse(input)?)
+/// Parse a connector joining the conditions of a filter, the inverse of the
+/// `Display` of `Operator::And` and `Operator::Or`, also accepting the
+/// words `and` and `or`
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let connector = parse_connector("|").unwrap();
+///     assert_eq!(connector, Operator::Or);
+///     assert_eq!(parse_connector("and").unwrap(), Operator::And);
+/// ```
+pub fn parse_connector(input: &str) -> Result<Operator, ParseError> {
+    match input {
+        "&" | "and" => Ok(Operator::And),
+        "|" | "or" => Ok(Operator::Or),
+        _ => {
+            Err(Error::GenericError(format!(
+                "Invalid connector: `{}`, expecting & or |",
+                input
+            ))
+            .into())
+        }
+    }
 }
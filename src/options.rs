@@ -0,0 +1,329 @@
+use crate::{
+    params::Params,
+    scan::{
+        scan,
+        split_unquoted,
+        VALUE_END,
+    },
+};
+use restq::{
+    ast::Direction,
+    Error,
+};
+
+/// Options which adjust how a url query is parsed
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let options = ParseOptions {
+///         default_page_size: 50,
+///         ..Default::default()
+///     };
+///     let query = parse_query_with("/person?age=lt.42&page=2", &options).unwrap();
+///     assert_eq!(query.to_string(), "person?age=lt.42&page=2&page_size=50");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// the page_size used when only `page` is specified
+    pub default_page_size: i64,
+    /// the character separating the parameters of the query, defaults to `&`.
+    /// Using `;` avoids having to percent encode the `&` in some clients.
+    /// This only affects the boundaries of the parameters, a `&` inside a
+    /// parameter is still an AND connector.
+    pub separator: char,
+    /// the boolean columns whose values may also be written as `1`/`0`,
+    /// `yes`/`no` or `on`/`off` in the filter, empty by default
+    pub lenient_bool_columns: Vec<String>,
+    /// how a condition without a value, such as `name=eq.`, is read
+    pub empty_value: EmptyValue,
+}
+
+/// How to read a condition without a value, ie: `name=eq.`, which forms
+/// tend to send for the fields left blank
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmptyValue {
+    /// the value is the empty string, as restq parses it, this is the default
+    String,
+    /// `eq.` is `is.null` and `neq.` is `is_not.null`, the other operators
+    /// are an error
+    Null,
+    /// a condition without a value is an error
+    Error,
+}
+
+/// The adjustments to the parsed query, for the parameters which restq
+/// doesn't have
+pub(crate) struct Rewrite {
+    /// the `order_by` has to be reversed, which is the case for `last`
+    pub reverse_order: bool,
+    /// the direction of the `order_by` entries without one, from `dir`
+    pub default_direction: Option<Direction>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            default_page_size: 20,
+            separator: '&',
+            lenient_bool_columns: vec![],
+            empty_value: EmptyValue::String,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// rewrite the parameters into the form restq parses, returning what
+    /// remains to be done on the parsed query
+    pub(crate) fn apply(&self, params: &mut Params) -> Result<Rewrite, Error> {
+        self.apply_page_defaults(params);
+        apply_range_header(params)?;
+        apply_fields(params)?;
+        apply_select(params)?;
+        self.apply_empty_values(params)?;
+        let reverse_order = apply_first_last(params)?;
+        let default_direction = match params.take("dir") {
+            Some(dir) => {
+                Some(
+                    crate::parse_direction(&dir)
+                        .map_err(|error| Error::GenericError(error.message))?,
+                )
+            }
+            None => None,
+        };
+        Ok(Rewrite {
+            reverse_order,
+            default_direction,
+        })
+    }
+
+    /// `page` defaults to 1 and `page_size` defaults to `default_page_size`
+    /// when only one of them is specified, both are then put at the end.
+    /// When both are specified they are left where they are.
+    fn apply_page_defaults(&self, params: &mut Params) {
+        if params.position("page").is_some()
+            == params.position("page_size").is_some()
+        {
+            return;
+        }
+        let page = params.take("page");
+        let page_size = params.take("page_size");
+        let page = page.unwrap_or_else(|| "1".to_string());
+        let page_size =
+            page_size.unwrap_or_else(|| self.default_page_size.to_string());
+        params.push("page", &page);
+        params.push("page_size", &page_size);
+    }
+
+    /// rewrite the conditions without a value according to `empty_value`
+    fn apply_empty_values(&self, params: &mut Params) -> Result<(), Error> {
+        if self.empty_value == EmptyValue::String {
+            return Ok(());
+        }
+        for param in params.params.iter_mut() {
+            let mut rewritten = String::new();
+            let mut rest = param.text.as_str();
+            while let Some((start, end)) = find_empty_value(rest) {
+                let operator = &rest[start..end - 1];
+                let replacement = match (self.empty_value, operator) {
+                    (EmptyValue::Null, "eq") => "is.null",
+                    (EmptyValue::Null, "neq") => "is_not.null",
+                    _ => {
+                        return Err(Error::GenericError(format!(
+                            "Missing the value of `{}`",
+                            param.text
+                        )))
+                    }
+                };
+                rewritten.push_str(&rest[..start]);
+                rewritten.push_str(replacement);
+                rest = &rest[end..];
+            }
+            rewritten.push_str(rest);
+            param.text = rewritten;
+        }
+        Ok(())
+    }
+}
+
+/// The byte range of the first `operator.` which is followed by no value,
+/// ie: the `eq.` of `name=eq.&age=lt.13`, skipping quoted strings
+fn find_empty_value(input: &str) -> Option<(usize, usize)> {
+    for scanned in scan(input) {
+        if !scanned.is('.') {
+            continue;
+        }
+        let i = scanned.index;
+        let is_empty = match input[i + 1..].chars().next() {
+            None => true,
+            Some(next) => VALUE_END.contains(next),
+        };
+        let before = &input[..i];
+        let start = before
+            .rfind(|c: char| !(c.is_ascii_lowercase() || c == '_'))
+            .map(|p| p + 1)
+            .unwrap_or(0);
+        if is_empty && start > 0 && start < i && before[..start].ends_with('=')
+        {
+            return Some((start, i + 1));
+        }
+    }
+    None
+}
+
+/// `range=0-24` mirrors the http Range header, where both ends are
+/// inclusive, which is `limit=25&offset=0`
+fn apply_range_header(params: &mut Params) -> Result<(), Error> {
+    if let Some(range) = params.take("range") {
+        let invalid = || {
+            Error::GenericError(format!(
+                "Invalid range: `{}`, expecting start-end, ie: 0-24",
+                range
+            ))
+        };
+        let mut bounds = range.splitn(2, '-');
+        let start: i64 = bounds
+            .next()
+            .and_then(|start| start.parse().ok())
+            .ok_or_else(invalid)?;
+        let end: i64 = bounds
+            .next()
+            .and_then(|end| end.parse().ok())
+            .ok_or_else(invalid)?;
+        if start < 0 || end < start {
+            return Err(invalid());
+        }
+        let limit = (end - start).checked_add(1).ok_or_else(invalid)?;
+        params.push("limit", &limit.to_string());
+        params.push("offset", &start.to_string());
+    }
+    Ok(())
+}
+
+/// `first=10` is `limit=10&offset=0`.
+///
+/// `last=10` is also `limit=10&offset=0`, but on the reversed `order_by`,
+/// so the last 10 rows are the ones fetched. These rows come in the reversed
+/// order, which the caller has to flip back if the original order matters.
+fn apply_first_last(params: &mut Params) -> Result<bool, Error> {
+    let first = params.take("first");
+    let last = params.take("last");
+    let (count, reverse) = match (first, last) {
+        (None, None) => return Ok(false),
+        (Some(first), None) => (first, false),
+        (None, Some(last)) => (last, true),
+        (Some(_), Some(_)) => {
+            return Err(Error::GenericError(
+                "first and last can't be used together".to_string(),
+            ))
+        }
+    };
+    let count: i64 = count
+        .parse()
+        .ok()
+        .filter(|count| *count >= 0)
+        .ok_or_else(|| {
+            Error::GenericError(format!(
+                "Invalid count: `{}`, expecting a positive number",
+                count
+            ))
+        })?;
+    params.push("limit", &count.to_string());
+    params.push("offset", "0");
+    Ok(reverse)
+}
+
+/// `fields=name,age` is the projection `/person{name,age}`, as are the
+/// JSON:API sparse fieldsets `fields[person]=name,age` for the table of the
+/// query. restq has no nested projections, so the fieldsets of other
+/// resources are an error.
+fn apply_fields(params: &mut Params) -> Result<(), Error> {
+    let table: String = params
+        .path
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|ch| ch.is_alphanumeric() || *ch == '_' || *ch == '.')
+        .collect();
+    let mut fields = params.take("fields");
+    while let Some(position) = params
+        .params
+        .iter()
+        .position(|p| p.text.starts_with("fields["))
+    {
+        let param = params.params.remove(position).text;
+        let (key, value) = param.split_at(param.find('=').unwrap_or(0));
+        let resource = key
+            .trim_start_matches("fields[")
+            .strip_suffix(']')
+            .unwrap_or_default();
+        if resource != table {
+            return Err(Error::GenericError(format!(
+                "Invalid fieldset: `{}`, only the fields of `{}` can be selected",
+                param, table
+            )));
+        }
+        fields = Some(value.trim_start_matches('=').to_string());
+    }
+    if let Some(fields) = fields {
+        if params.path.contains('{') || params.path.contains('(') {
+            return Err(Error::GenericError(
+                "The fields are already selected in the path".to_string(),
+            ));
+        }
+        params.path = format!("{}{{{}}}", params.path, fields);
+    }
+    Ok(())
+}
+
+/// `select=name,years:age,sum(score)` is the projection
+/// `/person{name,age=>years,sum(score)}`, where `alias:expr` is the
+/// PostgREST way of renaming a column. A literal value or a function is
+/// also renamed with `expr:alias`, ie: `1:one`, `'hi':greeting` or
+/// `sum(amount):total`. `select=*` selects every column, which is the same
+/// as having no projection.
+fn apply_select(params: &mut Params) -> Result<(), Error> {
+    if let Some(select) = params.take("select") {
+        if params.path.contains('{') || params.path.contains('(') {
+            return Err(Error::GenericError(
+                "The columns are already selected".to_string(),
+            ));
+        }
+        if select == "*" {
+            return Ok(());
+        }
+        let items: Vec<String> = split_unquoted(&select, ',')
+            .into_iter()
+            .map(|item| {
+                match split_alias(item) {
+                    Some((expr, alias)) => format!("{}=>{}", expr, alias),
+                    None => item.to_string(),
+                }
+            })
+            .collect();
+        params.path = format!("{}{{{}}}", params.path, items.join(","));
+    }
+    Ok(())
+}
+
+/// the `expr` and the `alias` of `alias:expr`, also accepting `expr:alias`
+/// when the `expr` can't be an alias, ie: `1:one`, `'hi':greeting` or
+/// `sum(amount):total`
+fn split_alias(item: &str) -> Option<(&str, &str)> {
+    let is_alias = |alias: &str| {
+        alias.starts_with(|ch: char| ch.is_alphabetic() || ch == '_')
+            && alias.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+    };
+    let colon = item.find(':')?;
+    let (alias, expr) = (&item[..colon], &item[colon + 1..]);
+    if is_alias(alias) {
+        return Some((expr, alias));
+    }
+    let colon = item.rfind(':')?;
+    let (expr, alias) = (&item[..colon], &item[colon + 1..]);
+    if !expr.is_empty() && !is_alias(expr) && is_alias(alias) {
+        Some((expr, alias))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,168 @@
+//! Splits the url into its path and its top-level parameters, so the
+//! parameters can be adjusted before the url is handed to restq.
+//!
+//! Each parameter keeps where it is in the input, for a position in the
+//! adjusted url to be reported as the position in the input.
+use crate::{
+    parse_error,
+    scan::scan,
+};
+
+/// the parameters that restq parses as a clause rather than a filter,
+/// these must not come first right after the `?`
+const CLAUSES: &[&str] = &[
+    "group_by",
+    "having",
+    "order_by",
+    "page",
+    "page_size",
+    "limit",
+    "offset",
+];
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Params {
+    /// the path part of the url, ie: `/person{name,age}`
+    pub path: String,
+    /// the separated parameters after the `?`
+    pub params: Vec<Param>,
+    /// the path as it is in the input, which starts with it
+    original_path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Param {
+    pub text: String,
+    /// the char offset of the parameter in the input and its text there,
+    /// `None` for a parameter added by a rewrite
+    origin: Option<(usize, String)>,
+}
+
+impl Params {
+    /// split the url on the `?` and the parameter separator which are not
+    /// inside a group, a function call or a quoted string
+    pub fn split(input: &str, separator: char) -> Self {
+        let mut segments = split_top_level(input, separator);
+        let (_, path) = segments.remove(0);
+        let params = segments
+            .into_iter()
+            .filter(|(_, segment)| !segment.is_empty())
+            .map(|(start, segment)| {
+                Param {
+                    text: segment.to_string(),
+                    origin: Some((
+                        input[..start].chars().count(),
+                        segment.to_string(),
+                    )),
+                }
+            })
+            .collect();
+        Params {
+            path: path.to_string(),
+            params,
+            original_path: path.to_string(),
+        }
+    }
+
+    /// the parameters which are not in an input, ie: from key value pairs
+    pub fn new(path: &str, params: Vec<String>) -> Self {
+        Params {
+            path: path.to_string(),
+            params: params
+                .into_iter()
+                .map(|text| Param { text, origin: None })
+                .collect(),
+            original_path: path.to_string(),
+        }
+    }
+
+    /// remove the first parameter with this key, returning its value
+    pub fn take(&mut self, key: &str) -> Option<String> {
+        let index = self.position(key)?;
+        let param = self.params.remove(index);
+        Some(param.text[key.len() + 1..].to_string())
+    }
+
+    /// the index of the first parameter with this key
+    pub fn position(&self, key: &str) -> Option<usize> {
+        let prefix = format!("{}=", key);
+        self.params.iter().position(|p| p.text.starts_with(&prefix))
+    }
+
+    pub fn push(&mut self, key: &str, value: &str) {
+        self.params.push(Param {
+            text: format!("{}={}", key, value),
+            origin: None,
+        });
+    }
+
+    /// reassemble the url in the form restq expects
+    pub fn to_url(&self) -> String {
+        let mut url = self.path.clone();
+        if let Some(first) = self.params.first() {
+            url.push('?');
+            if is_clause(&first.text) {
+                url.push('&');
+            }
+            let params: Vec<&str> =
+                self.params.iter().map(|p| p.text.as_str()).collect();
+            url.push_str(&params.join("&"));
+        }
+        url
+    }
+
+    /// The char offset in the input of the char at `position` in `to_url`,
+    /// `None` when it is in a parameter added by a rewrite. A separator
+    /// after a parameter is at the end of that parameter in the input.
+    pub fn input_position(&self, position: usize) -> Option<usize> {
+        let path = (&self.path, Some((0, &self.original_path)));
+        let params = self.params.iter().map(|param| {
+            (
+                &param.text,
+                param.origin.as_ref().map(|(at, text)| (*at, text)),
+            )
+        });
+        let pieces: Vec<_> = std::iter::once(path).chain(params).collect();
+        let mut start = 0;
+        for (i, (text, origin)) in pieces.iter().enumerate() {
+            let separators = match i {
+                0 if pieces.len() > 1 && is_clause(&self.params[0].text) => 2,
+                _ if i + 1 < pieces.len() => 1,
+                _ => 0,
+            };
+            let end = start + text.chars().count() + separators;
+            if position < end || i + 1 == pieces.len() {
+                return origin.map(|(at, original)| {
+                    at + parse_error::align(original, text, position - start)
+                });
+            }
+            start = end;
+        }
+        None
+    }
+}
+
+fn is_clause(param: &str) -> bool {
+    CLAUSES
+        .iter()
+        .any(|clause| param.starts_with(&format!("{}=", clause)))
+}
+
+/// Split the input on the first `?` and on every separator which is not
+/// inside a group, a function call or a quoted string, each segment with
+/// the byte index where it starts.
+fn split_top_level(input: &str, separator: char) -> Vec<(usize, &str)> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut in_query = false;
+    for scanned in scan(input) {
+        let is_query = scanned.is('?') && !in_query;
+        if (is_query || scanned.is(separator)) && scanned.depth == 0 {
+            in_query |= is_query;
+            segments.push((start, &input[start..scanned.index]));
+            start = scanned.index + scanned.ch.len_utf8();
+        }
+    }
+    segments.push((start, &input[start..]));
+    segments
+}
@@ -0,0 +1,103 @@
+use restq::{
+    pom,
+    Error,
+};
+use thiserror::Error;
+
+/// A parse failure with the position where it was detected, for reporting
+/// back to the client which part of its query is wrong
+///
+/// Example:
+/// ```rust
+///     use inquerest::*;
+///
+///     let error = parse_query("/person?age=lt.13&(grade=eq.2").unwrap_err();
+///     assert_eq!(error.position, Some(18));
+///
+///     // the position is in the input, before it was rewritten for restq
+///     let error =
+///         parse_query("/person?AGE=lt.13&day=eq.2023-01-01&(grade=eq.2&first=10")
+///             .unwrap_err();
+///     assert_eq!(error.position, Some(36));
+///     // as is the message, which doesn't repeat the position
+///     assert!(error.message.ends_with("found: ("));
+///     assert!(!error.message.contains("at 38"));
+/// ```
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{message}")]
+pub struct ParseError {
+    /// the char offset in the parsed input where the parser stopped, `None`
+    /// when the error is not tied to a position, ie: an invalid `range`, or
+    /// is in a parameter which isn't in the input, such as the `limit` which
+    /// `first` is rewritten into.
+    pub position: Option<usize>,
+    /// what was expected at the position
+    pub message: String,
+}
+
+impl From<Error> for ParseError {
+    fn from(error: Error) -> Self {
+        let position = match &error {
+            Error::ParseError(error) => pom_position(error),
+            _ => None,
+        };
+        let message = match error {
+            Error::ParseError(error) => pom_message(&error),
+            Error::GenericError(message) => message,
+            error => error.to_string(),
+        };
+        ParseError { position, message }
+    }
+}
+
+fn pom_position(error: &pom::Error) -> Option<usize> {
+    match error {
+        pom::Error::Incomplete => None,
+        pom::Error::Mismatch { position, .. }
+        | pom::Error::Conversion { position, .. }
+        | pom::Error::Expect { position, .. }
+        | pom::Error::Custom { position, .. } => Some(*position),
+    }
+}
+
+/// the message of the pom error without its position, which is in the
+/// rewritten input rather than in the input
+fn pom_message(error: &pom::Error) -> String {
+    match error {
+        pom::Error::Incomplete => "Incomplete".to_string(),
+        pom::Error::Mismatch { message, .. }
+        | pom::Error::Conversion { message, .. }
+        | pom::Error::Expect { message, .. }
+        | pom::Error::Custom { message, .. } => message.to_string(),
+    }
+}
+
+impl ParseError {
+    /// map the position in the rewritten input back to the input
+    pub(crate) fn map_position<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(usize) -> Option<usize>,
+    {
+        self.position = self.position.and_then(f);
+        self
+    }
+}
+
+/// The char offset in `original` of the char at `position` in `rewritten`,
+/// which is the original with some chars inserted, ie: the quotes around a
+/// date, and the keywords lowercased. An inserted char is at the position
+/// of the original char which follows it.
+pub(crate) fn align(original: &str, rewritten: &str, position: usize) -> usize {
+    let original: Vec<char> = original.chars().collect();
+    let mut at = 0;
+    for ch in rewritten.chars().take(position) {
+        if original
+            .get(at)
+            .map(|original| original.eq_ignore_ascii_case(&ch))
+            .unwrap_or(false)
+        {
+            at += 1;
+        }
+    }
+    at
+}
@@ -0,0 +1,234 @@
+//! Rendering of a parsed `Select` into the query string conventions of
+//! PostgREST, so a query can be proxied to a PostgREST backend
+use crate::{
+    expr,
+    select,
+};
+use restq::{
+    ast::{
+        BinaryOperation,
+        Expr,
+        Operator,
+        Range,
+        Select,
+        Value,
+    },
+    Error,
+};
+
+/// Render the select as a PostgREST url, ie:
+/// `/person?select=name,years:age&age=lt.13&or=(student.eq.true,gender.eq.M)&order=age.desc`
+///
+/// Joins, group_by, having, functions and comparisons between two
+/// columns have no PostgREST equivalent and are an error.
+///
+/// The column names and the values are percent encoded, so a value such as
+/// `'x&role=eq.admin'` stays a single value and isn't read as another
+/// parameter.
+pub fn to_postgrest_string(select: &Select) -> Result<String, Error> {
+    if select.from_table.join.is_some() {
+        return unsupported("joined tables");
+    }
+    if select.group_by.is_some() || select.having.is_some() {
+        return unsupported("group_by and having");
+    }
+    let mut params = vec![];
+    if let Some(projection) = &select.projection {
+        let columns = projection
+            .iter()
+            .map(|expr_rename| {
+                let column = match &expr_rename.expr {
+                    Expr::Column(column) => encode(&column.name),
+                    _ => {
+                        return unsupported("selecting non-column expressions")
+                    }
+                };
+                Ok(match &expr_rename.rename {
+                    Some(rename) => format!("{}:{}", encode(rename), column),
+                    None => column,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        params.push(format!("select={}", columns.join(",")));
+    }
+    if let Some(filter) = &select.filter {
+        for conjunct in expr::conjuncts(filter) {
+            params.push(top_level_filter(conjunct)?);
+        }
+    }
+    if let Some(order_by) = &select.order_by {
+        let orders = order_by
+            .iter()
+            .map(|order| {
+                let column = match &order.expr {
+                    Expr::Column(column) => encode(&column.name),
+                    _ => {
+                        return unsupported(
+                            "ordering by non-column expressions",
+                        )
+                    }
+                };
+                Ok(match &order.direction {
+                    Some(direction) => format!("{}.{}", column, direction),
+                    None => column,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        params.push(format!("order={}", orders.join(",")));
+    }
+    match &select.range {
+        Some(Range::Page(page)) => {
+            let offset = select::page_offset(page)?;
+            params.push(format!("limit={}", page.page_size));
+            params.push(format!("offset={}", offset));
+        }
+        Some(Range::Limit(limit)) => {
+            params.push(format!("limit={}", limit.limit));
+            if let Some(offset) = limit.offset {
+                params.push(format!("offset={}", offset));
+            }
+        }
+        None => (),
+    }
+
+    let mut url = format!("/{}", encode(&select.from_table.from.name));
+    if !params.is_empty() {
+        url.push('?');
+        url.push_str(&params.join("&"));
+    }
+    Ok(url)
+}
+
+fn unsupported<T>(what: &str) -> Result<T, Error> {
+    Err(Error::GenericError(format!(
+        "PostgREST doesn't support {}",
+        what
+    )))
+}
+
+/// a filter parameter, `age=lt.13` or a logic tree `or=(...)`
+fn top_level_filter(expr: &Expr) -> Result<String, Error> {
+    match expr {
+        Expr::Nested(expr) => top_level_filter(expr),
+        Expr::BinaryOperation(binop) if binop.operator == Operator::Or => {
+            Ok(format!("or=({})", logic_items(expr, &Operator::Or)?))
+        }
+        Expr::BinaryOperation(binop) => {
+            let (column, condition) = condition(binop, false)?;
+            Ok(format!("{}={}", column, condition))
+        }
+        _ => unsupported("filters which are not a comparison"),
+    }
+}
+
+/// the comma separated items of an `and(...)` or `or(...)` logic tree
+fn logic_items(expr: &Expr, connector: &Operator) -> Result<String, Error> {
+    match expr {
+        Expr::Nested(expr) => logic_items(expr, connector),
+        Expr::BinaryOperation(binop) if binop.operator == *connector => {
+            Ok(format!(
+                "{},{}",
+                logic_items(&binop.left, connector)?,
+                logic_items(&binop.right, connector)?
+            ))
+        }
+        Expr::BinaryOperation(binop) if binop.operator == Operator::And => {
+            Ok(format!("and({})", logic_items(expr, &Operator::And)?))
+        }
+        Expr::BinaryOperation(binop) if binop.operator == Operator::Or => {
+            Ok(format!("or({})", logic_items(expr, &Operator::Or)?))
+        }
+        Expr::BinaryOperation(binop) => {
+            let (column, condition) = condition(binop, true)?;
+            Ok(format!("{}.{}", column, condition))
+        }
+        _ => unsupported("filters which are not a comparison"),
+    }
+}
+
+/// the column and the `operator.value` of a comparison
+fn condition(
+    binop: &BinaryOperation,
+    in_logic: bool,
+) -> Result<(String, String), Error> {
+    let mut binop = binop.clone();
+    if let Expr::Value(_) = binop.left {
+        expr::swap_operands(&mut binop);
+    }
+    let (column, value) = match (&binop.left, &binop.right) {
+        (Expr::Column(column), Expr::Value(value)) => (&column.name, value),
+        _ => return unsupported("comparisons other than column to value"),
+    };
+    let value = match binop.operator {
+        Operator::Like | Operator::Ilike | Operator::Starts => {
+            pattern(value, &binop.operator)
+        }
+        _ => plain_value(value),
+    };
+    let value = match binop.operator {
+        Operator::In | Operator::NotIn => encode(&quote_reserved(&value)),
+        _ if in_logic => encode(&quote_reserved(&value)),
+        _ => encode(&value),
+    };
+    let condition = match binop.operator {
+        Operator::Eq => format!("eq.{}", value),
+        Operator::Neq => format!("neq.{}", value),
+        Operator::Lt => format!("lt.{}", value),
+        Operator::Lte => format!("lte.{}", value),
+        Operator::Gt => format!("gt.{}", value),
+        Operator::Gte => format!("gte.{}", value),
+        Operator::Like => format!("like.{}", value),
+        Operator::Ilike | Operator::Starts => format!("ilike.{}", value),
+        Operator::In => format!("in.({})", value),
+        Operator::NotIn => format!("not.in.({})", value),
+        Operator::Is => format!("is.{}", value),
+        Operator::IsNot => format!("not.is.{}", value),
+        _ => return unsupported(&format!("the operator `{}`", binop.operator)),
+    };
+    Ok((encode(column), condition))
+}
+
+fn plain_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::String(v) => v.to_string(),
+        Value::Number(v) => v.to_string(),
+        Value::Bool(v) => v.to_string(),
+    }
+}
+
+/// PostgREST uses `*` as the wildcard of like patterns
+fn pattern(value: &Value, operator: &Operator) -> String {
+    let pattern = plain_value(value).replace('%', "*");
+    match operator {
+        Operator::Starts => format!("{}*", pattern),
+        _ => pattern,
+    }
+}
+
+/// values inside a logic tree or an `in` list are double quoted when they
+/// contain the characters PostgREST reserves there
+fn quote_reserved(value: &str) -> String {
+    if value.chars().any(|ch| ",.:() ".contains(ch)) {
+        format!("\"{}\"", value.replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// percent encode the characters which would end or alter a parameter, ie:
+/// `&`, `=`, `#` or `+`, the spaces and the non ascii characters
+fn encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for ch in input.chars() {
+        if ch.is_ascii_graphic() && !"%&=#+?".contains(ch) {
+            encoded.push(ch);
+        } else {
+            let mut bytes = [0; 4];
+            for byte in ch.encode_utf8(&mut bytes).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,118 @@
+//! The scanning shared by the rewrites which run before restq parses the
+//! input: which characters are part of a quoted string and how deeply the
+//! others are nested in brackets.
+//!
+//! The quoted strings are the ones restq reads, `'..'`, `".."` and
+//! `` `..` ``, where a `\` escapes the next character.
+
+/// the characters which end an unquoted value, ie: the `13` of
+/// `age=lt.13&...`, `(age=lt.13)` or `in.(13,14)`
+pub(crate) const VALUE_END: &str = "&|),";
+
+/// A character of the scanned input
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Scanned {
+    /// the byte index of the character
+    pub index: usize,
+    pub ch: char,
+    /// whether the character is part of a quoted string, the quotes included
+    pub quoted: bool,
+    /// the number of open brackets, `(`, `{` or `[`, outside of the quoted
+    /// strings. A bracket is counted as open at itself, so a bracket and
+    /// the one closing it have the same depth.
+    pub depth: usize,
+}
+
+impl Scanned {
+    /// whether this is the character `ch` outside of any quoted string
+    pub fn is(&self, ch: char) -> bool {
+        !self.quoted && self.ch == ch
+    }
+}
+
+/// Scan each character of the input
+pub(crate) fn scan(input: &str) -> Scanner<'_> {
+    Scanner {
+        chars: input.char_indices(),
+        quote: None,
+        escaped: false,
+        depth: 0,
+    }
+}
+
+pub(crate) struct Scanner<'a> {
+    chars: std::str::CharIndices<'a>,
+    quote: Option<char>,
+    escaped: bool,
+    depth: usize,
+}
+
+impl Scanner<'_> {
+    /// whether the scanned characters end inside a quoted string, which
+    /// isn't closed
+    pub fn in_quote(&self) -> bool {
+        self.quote.is_some()
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Scanned;
+
+    fn next(&mut self) -> Option<Scanned> {
+        let (index, ch) = self.chars.next()?;
+        let quoted = match self.quote {
+            Some(_) if self.escaped => {
+                self.escaped = false;
+                true
+            }
+            Some(_) if ch == '\\' => {
+                self.escaped = true;
+                true
+            }
+            Some(q) => {
+                if ch == q {
+                    self.quote = None;
+                }
+                true
+            }
+            None if ch == '\'' || ch == '"' || ch == '`' => {
+                self.quote = Some(ch);
+                true
+            }
+            None => false,
+        };
+        let depth = match ch {
+            '(' | '{' | '[' if !quoted => {
+                self.depth += 1;
+                self.depth
+            }
+            ')' | '}' | ']' if !quoted => {
+                let depth = self.depth;
+                self.depth = self.depth.saturating_sub(1);
+                depth
+            }
+            _ => self.depth,
+        };
+        Some(Scanned {
+            index,
+            ch,
+            quoted,
+            depth,
+        })
+    }
+}
+
+/// Split the input on the separators which are neither quoted nor inside
+/// brackets, ie: the commas of `name,max(age,1),'a,b'` give three items
+pub(crate) fn split_unquoted(input: &str, separator: char) -> Vec<&str> {
+    let mut items = vec![];
+    let mut start = 0;
+    for scanned in scan(input) {
+        if scanned.is(separator) && scanned.depth == 0 {
+            items.push(&input[start..scanned.index]);
+            start = scanned.index + separator.len_utf8();
+        }
+    }
+    items.push(&input[start..]);
+    items
+}
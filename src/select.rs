@@ -0,0 +1,449 @@
+//! Operations on a parsed `Select` which restq doesn't provide
+use crate::expr;
+use restq::{
+    ast::{
+        BinaryOperation,
+        Direction,
+        Expr,
+        Operator,
+        Order,
+        Page,
+        Select,
+        Value,
+    },
+    Error,
+};
+use std::ops::Bound;
+
+/// Call `f` on each of the top-level expressions of the select, in the
+/// order they appear in the generated sql: projection, filter, group_by,
+/// having and then order_by
+pub fn for_each_expr<'a, F>(select: &'a Select, mut f: F)
+where
+    F: FnMut(&'a Expr),
+{
+    if let Some(projection) = select.projection.as_ref() {
+        for expr_rename in projection.iter() {
+            f(&expr_rename.expr);
+        }
+    }
+    if let Some(filter) = select.filter.as_ref() {
+        f(filter);
+    }
+    if let Some(group_by) = select.group_by.as_ref() {
+        for expr in group_by.iter() {
+            f(expr);
+        }
+    }
+    if let Some(having) = select.having.as_ref() {
+        f(having);
+    }
+    if let Some(order_by) = select.order_by.as_ref() {
+        for order in order_by.iter() {
+            f(&order.expr);
+        }
+    }
+}
+
+/// The mutable counterpart of `for_each_expr`
+pub fn for_each_expr_mut<F>(select: &mut Select, mut f: F)
+where
+    F: FnMut(&mut Expr),
+{
+    if let Some(projection) = select.projection.as_mut() {
+        for expr_rename in projection.iter_mut() {
+            f(&mut expr_rename.expr);
+        }
+    }
+    if let Some(filter) = select.filter.as_mut() {
+        f(filter);
+    }
+    if let Some(group_by) = select.group_by.as_mut() {
+        for expr in group_by.iter_mut() {
+            f(expr);
+        }
+    }
+    if let Some(having) = select.having.as_mut() {
+        f(having);
+    }
+    if let Some(order_by) = select.order_by.as_mut() {
+        for order in order_by.iter_mut() {
+            f(&mut order.expr);
+        }
+    }
+}
+
+/// The literal values of the select, in the order a sql generator emits
+/// them, so they can be supplied as the parameters of a prepared statement
+pub fn bind_values(select: &Select) -> Vec<&Value> {
+    let mut values = vec![];
+    for_each_expr(select, |expr| values.extend(expr::bind_values(expr)));
+    values
+}
+
+/// Apply `f` in place to every literal value in the select, ie: to
+/// sanitize or normalize the values supplied by the client
+pub fn map_values<F>(select: &mut Select, mut f: F)
+where
+    F: FnMut(&mut Value),
+{
+    for_each_expr_mut(select, |expr| expr::map_values(expr, &mut f))
+}
+
+/// Lowercase the names of all the functions used in the select
+pub fn normalize_function_names(select: &mut Select) {
+    for_each_expr_mut(select, expr::normalize_function_names)
+}
+
+/// The names of the functions used in the select, including the ones
+/// nested in the arguments of another function, each listed once in the
+/// order they first appear, ie: to reject the dangerous ones.
+///
+/// The names are as written, see `normalize_function_names` to compare
+/// them case-insensitively.
+pub fn referenced_functions(select: &Select) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for_each_expr(select, |clause| {
+        expr::walk(clause, &mut |expr| {
+            if let Expr::Function(function) = expr {
+                if !names.contains(&function.name) {
+                    names.push(function.name.to_string());
+                }
+            }
+        })
+    });
+    names
+}
+
+/// Remove the `order_by` entries which sort on an expression that is
+/// already sorted by an earlier entry, keeping the first occurrence.
+///
+/// Returns the dropped entries, so the caller can warn about the
+/// conflicting ones, ie: `age.asc,age.desc`
+pub fn dedup_order_by(select: &mut Select) -> Vec<Order> {
+    let mut dropped = vec![];
+    if let Some(order_by) = select.order_by.take() {
+        let mut kept: Vec<Order> = vec![];
+        for order in order_by {
+            if kept.iter().any(|k| k.expr == order.expr) {
+                dropped.push(order);
+            } else {
+                kept.push(order);
+            }
+        }
+        select.order_by = Some(kept);
+    }
+    dropped
+}
+
+/// Append the `extra` entries to the `order_by`, such as a tie-breaker
+/// enforced by the server for a stable pagination. The entries already in
+/// the `order_by` take precedence, an extra entry on an expression which is
+/// already sorted on is dropped.
+pub fn merge_order_by(select: &mut Select, extra: Vec<Order>) {
+    let order_by = select.order_by.get_or_insert_with(Vec::new);
+    for order in extra {
+        if !order_by.iter().any(|existing| existing.expr == order.expr) {
+            order_by.push(order);
+        }
+    }
+}
+
+/// Reverse the direction of every `order_by` entry, an entry without a
+/// direction sorts ascending so it becomes descending.
+pub fn reverse_order_by(select: &mut Select) {
+    if let Some(order_by) = select.order_by.as_mut() {
+        for order in order_by {
+            order.direction = match order.direction {
+                Some(Direction::Desc) => Some(Direction::Asc),
+                Some(Direction::Asc) | None => Some(Direction::Desc),
+            };
+        }
+    }
+}
+
+/// Replace the `order_by` entries which refer to the rename of a projected
+/// expression with the expression itself, so `{sum(amount)=>total}` with
+/// `order_by=total.desc` sorts on `sum(amount)`.
+///
+/// As in SQL, a rename takes precedence over a column of the same name.
+pub fn resolve_order_by_aliases(select: &mut Select) {
+    let (projection, order_by) =
+        match (select.projection.as_ref(), select.order_by.as_mut()) {
+            (Some(projection), Some(order_by)) => (projection, order_by),
+            _ => return,
+        };
+    for order in order_by.iter_mut() {
+        if let Expr::Column(column) = &order.expr {
+            let renamed = projection.iter().find(|expr_rename| {
+                expr_rename.rename.as_ref() == Some(&column.name)
+            });
+            if let Some(expr_rename) = renamed {
+                order.expr = expr_rename.expr.clone();
+            }
+        }
+    }
+}
+
+/// Replace the positions on the left side of the `having` conditions with
+/// the projected expression they refer to, counting from 1, so
+/// `{grade,sum(amount)}` with `having=2=gt.100` is `sum(amount)=gt.100`.
+///
+/// It is an error to refer to a position which is not in the projection.
+pub fn resolve_having_ordinals(select: &mut Select) -> Result<(), Error> {
+    let having = match select.having.as_mut() {
+        Some(having) => having,
+        None => return Ok(()),
+    };
+    let count = select.projection.as_ref().map(Vec::len).unwrap_or(0);
+    for condition in expr::conditions_mut(having) {
+        let position = match condition.left {
+            Expr::Value(Value::Number(n)) if n.fract() == 0.0 && n >= 1.0 => {
+                n as usize
+            }
+            _ => continue,
+        };
+        let expr_rename = select
+            .projection
+            .as_ref()
+            .and_then(|projection| projection.get(position - 1))
+            .ok_or_else(|| {
+                Error::GenericError(format!(
+                    "having refers to the position {}, but {} expressions are selected",
+                    position, count
+                ))
+            })?;
+        condition.left = expr_rename.expr.clone();
+    }
+    Ok(())
+}
+
+/// Compare two queries ignoring their `range`, so queries that only differ
+/// in the requested page are considered the same result set.
+pub fn eq_ignoring_range(select: &Select, other: &Select) -> bool {
+    select.from_table == other.from_table
+        && select.filter == other.filter
+        && select.group_by == other.group_by
+        && select.having == other.having
+        && select.projection == other.projection
+        && select.order_by == other.order_by
+}
+
+/// Append a condition to the filter of the select, joined with the
+/// connector, which must be either `Operator::And` or `Operator::Or`.
+/// The condition becomes the filter when there is none yet.
+///
+/// A side which contains the other connector is grouped in a parenthesis,
+/// so `a|b` with `c` appended using AND becomes `(a|b)&c`, as does `a&b|c`.
+pub fn push_filter(
+    select: &mut Select,
+    condition: Expr,
+    connector: Operator,
+) -> Result<(), Error> {
+    if connector != Operator::And && connector != Operator::Or {
+        return Err(Error::GenericError(format!(
+            "Expecting `&` or `|` as connector, found: `{}`",
+            connector
+        )));
+    }
+    let filter = match select.filter.take() {
+        Some(filter) => {
+            Expr::BinaryOperation(Box::new(BinaryOperation {
+                left: group_other_connector(filter, &connector),
+                right: group_other_connector(condition, &connector),
+                operator: connector,
+            }))
+        }
+        None => condition,
+    };
+    select.filter = Some(filter);
+    Ok(())
+}
+
+/// AND the filter of the other query into the filter of this one, such as
+/// when combining the filters of several layers.
+///
+/// The conditions joined by AND at the top level of the other filter which
+/// are already in this filter are dropped, so the resulting query doesn't
+/// filter on the same condition twice.
+pub fn merge_filter(select: &mut Select, other: &Select) {
+    let other = match &other.filter {
+        Some(other) => other,
+        None => return,
+    };
+    let mut existing: Vec<Expr> = select
+        .filter
+        .as_ref()
+        .map(|filter| expr::conjuncts(filter).into_iter().cloned().collect())
+        .unwrap_or_default();
+    for condition in expr::conjuncts(other) {
+        if !existing.contains(condition) {
+            existing.push(condition.clone());
+            push_filter(select, condition.clone(), Operator::And)
+                .expect("And is a valid connector");
+        }
+    }
+}
+
+/// The offset of the first row of the page, `(page - 1) * page_size`,
+/// which is an error for a page before the first one, a negative page_size
+/// or an offset which overflows
+pub(crate) fn page_offset(page: &Page) -> Result<i64, Error> {
+    if page.page < 1 || page.page_size < 0 {
+        return Err(Error::GenericError(format!(
+            "Invalid page: `{}` of page_size `{}`, the first page is 1",
+            page.page, page.page_size
+        )));
+    }
+    (page.page - 1).checked_mul(page.page_size).ok_or_else(|| {
+        Error::GenericError(format!(
+            "Invalid page: `{}` of page_size `{}`, the offset overflows",
+            page.page, page.page_size
+        ))
+    })
+}
+
+/// AND a row-level security policy into the filter of the query.
+///
+/// The policy is the outermost condition and the client filter is grouped
+/// in a parenthesis whenever it has a connector, so `a|b` with the policy
+/// `p` becomes `p&(a|b)` and a client OR, at whatever depth, can't weaken
+/// the policy.
+pub fn rewrite_with_policy(select: &mut Select, policy: &Expr) {
+    let policy = group_other_connector(policy.clone(), &Operator::And);
+    let filter = match select.filter.take() {
+        Some(filter) => {
+            Expr::BinaryOperation(Box::new(BinaryOperation {
+                left: policy,
+                operator: Operator::And,
+                right: group_connected(filter),
+            }))
+        }
+        None => policy,
+    };
+    select.filter = Some(filter);
+}
+
+/// wrap the expression in a parenthesis if it is joined by a connector
+fn group_connected(expr: Expr) -> Expr {
+    match &expr {
+        Expr::BinaryOperation(binop)
+            if binop.operator == Operator::And
+                || binop.operator == Operator::Or =>
+        {
+            Expr::Nested(Box::new(expr))
+        }
+        _ => expr,
+    }
+}
+
+/// wrap the expression in a parenthesis if it contains a connector other
+/// than this one which is not already grouped, at any depth. restq's sql
+/// conversion only emits the parenthesis of an `Expr::Nested`, so appending
+/// to `a&b|c` without grouping it would lose the precedence of its `|`.
+fn group_other_connector(expr: Expr, connector: &Operator) -> Expr {
+    if has_other_connector(&expr, connector) {
+        Expr::Nested(Box::new(expr))
+    } else {
+        expr
+    }
+}
+
+fn has_other_connector(expr: &Expr, connector: &Operator) -> bool {
+    match expr {
+        Expr::BinaryOperation(binop)
+            if binop.operator == Operator::And
+                || binop.operator == Operator::Or =>
+        {
+            binop.operator != *connector
+                || has_other_connector(&binop.left, connector)
+                || has_other_connector(&binop.right, connector)
+        }
+        _ => false,
+    }
+}
+
+/// The lower and upper bound on the column, from the comparisons on it
+/// that are joined by AND at the top level of the filter,
+/// ie: `age=gt.18&age=lte.65` gives `(Excluded(18), Included(65))`.
+///
+/// When a side is compared more than once, the first comparison is used.
+/// Returns `None` when the column has no such comparison.
+pub fn range_conditions_for(
+    select: &Select,
+    column: &str,
+) -> Option<(Bound<Value>, Bound<Value>)> {
+    let filter = select.filter.as_ref()?;
+    let mut lower = Bound::Unbounded;
+    let mut upper = Bound::Unbounded;
+    for conjunct in expr::conjuncts(filter) {
+        let mut binop = match conjunct {
+            Expr::BinaryOperation(binop) => binop.as_ref().clone(),
+            _ => continue,
+        };
+        if let Expr::Value(_) = binop.left {
+            expr::swap_operands(&mut binop);
+        }
+        let value = match (&binop.left, &binop.right) {
+            (Expr::Column(c), Expr::Value(value)) if c.name == column => {
+                value.clone()
+            }
+            _ => continue,
+        };
+        match binop.operator {
+            Operator::Gt if lower == Bound::Unbounded => {
+                lower = Bound::Excluded(value)
+            }
+            Operator::Gte if lower == Bound::Unbounded => {
+                lower = Bound::Included(value)
+            }
+            Operator::Lt if upper == Bound::Unbounded => {
+                upper = Bound::Excluded(value)
+            }
+            Operator::Lte if upper == Bound::Unbounded => {
+                upper = Bound::Included(value)
+            }
+            _ => (),
+        }
+    }
+    if lower == Bound::Unbounded && upper == Bound::Unbounded {
+        None
+    } else {
+        Some((lower, upper))
+    }
+}
+
+/// Rename the table in the from, in the joined tables and in the
+/// table-qualified column names, ie: `person.age` becomes `people.age`
+pub fn rename_table(select: &mut Select, from: &str, to: &str) {
+    let mut from_table = Some(&mut select.from_table);
+    while let Some(table) = from_table {
+        if table.from.name == from {
+            table.from.name = to.to_string();
+        }
+        from_table = table.join.as_mut().map(|(_, joined)| joined.as_mut());
+    }
+
+    let prefix = format!("{}.", from);
+    for_each_expr_mut(select, |expr| {
+        expr::walk_mut(expr, &mut |expr| {
+            if let Expr::Column(column) = expr {
+                if column.name.starts_with(&prefix) {
+                    column.name =
+                        format!("{}.{}", to, &column.name[prefix.len()..]);
+                }
+            }
+        })
+    });
+}
+
+/// The sql of the query with every literal value replaced by a `?`
+/// placeholder, which keeps the shape of the query, its columns, operators,
+/// grouping and ordering, without the data, ie: for logging.
+///
+/// The `null` of a null check is kept, since it is part of the structure.
+/// The sql is rendered as `sql::to_sql` does for Postgres, so every operator
+/// has its sql form and joins, which need a table lookup, are an error.
+pub fn sql_skeleton(select: &Select) -> Result<String, Error> {
+    crate::sql::to_sql_skeleton(select)
+}
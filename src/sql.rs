@@ -0,0 +1,368 @@
+//! Rendering of a parsed `Select` as sql text, for the dialect specific
+//! parts that restq's conversion into sql-ast doesn't cover
+use crate::expr;
+use restq::{
+    ast::{
+        BinaryOperation,
+        Column,
+        Direction,
+        Expr,
+        Operator,
+        Range,
+        Select,
+        Value,
+    },
+    Error,
+};
+
+/// The sql dialect to render for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dialect {
+    Postgres,
+    Mysql,
+}
+
+impl Dialect {
+    /// Quote an identifier with the delimiters of the dialect, doubling the
+    /// delimiter when it is part of the identifier, ie: `"name"` for
+    /// Postgres and `` `name` `` for Mysql
+    pub fn quote_identifier(&self, ident: &str) -> String {
+        let delimiter = match self {
+            Dialect::Postgres => '"',
+            Dialect::Mysql => '`',
+        };
+        let escaped =
+            ident.replace(delimiter, &format!("{}{}", delimiter, delimiter));
+        format!("{}{}{}", delimiter, escaped, delimiter)
+    }
+
+    /// Whether the operator can be rendered in this dialect, `ilike` and
+    /// `starts`, which is rendered as an `ILIKE`, only exist in Postgres
+    pub fn supports(&self, operator: &Operator) -> bool {
+        match self {
+            Dialect::Postgres => true,
+            Dialect::Mysql => {
+                *operator != Operator::Ilike && *operator != Operator::Starts
+            }
+        }
+    }
+
+    /// Quote a string literal, Mysql also treats the backslash as an escape
+    fn quote_string(&self, value: &str) -> String {
+        let escaped = match self {
+            Dialect::Postgres => value.replace('\'', "''"),
+            Dialect::Mysql => value.replace('\\', "\\\\").replace('\'', "''"),
+        };
+        format!("'{}'", escaped)
+    }
+}
+
+/// Render the column as a quoted identifier, quoting the table and the
+/// column separately when the column is qualified, ie: `"person"."age"`
+pub fn render_column(column: &Column, dialect: Dialect) -> String {
+    quote_qualified(&column.name, dialect)
+}
+
+fn quote_qualified(name: &str, dialect: Dialect) -> String {
+    name.split('.')
+        .map(|part| dialect.quote_identifier(part))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Render the select as a sql SELECT statement for the dialect, ie:
+/// `SELECT * FROM "person" WHERE "age" < 42 AND ("student" = TRUE OR "gender" = 'M')`
+///
+/// Unlike restq's `into_sql_statement`, every operator is rendered, and the
+/// grouping of the `&` and `|` connectors is kept as it was parsed.
+/// Joins need the foreign keys of restq's table lookup for their `ON`
+/// constraint, so they are an error here.
+pub fn to_sql(select: &Select, dialect: Dialect) -> Result<String, Error> {
+    render_select(
+        select,
+        Render {
+            dialect,
+            placeholders: false,
+        },
+    )
+}
+
+/// The sql of the select with every literal value but `null` rendered as a
+/// `?` placeholder, for `select::sql_skeleton`
+pub(crate) fn to_sql_skeleton(select: &Select) -> Result<String, Error> {
+    render_select(
+        select,
+        Render {
+            dialect: Dialect::Postgres,
+            placeholders: true,
+        },
+    )
+}
+
+/// How the expressions are rendered
+#[derive(Clone, Copy)]
+struct Render {
+    dialect: Dialect,
+    /// the literal values, other than `null`, are a `?` placeholder
+    placeholders: bool,
+}
+
+fn render_select(select: &Select, render: Render) -> Result<String, Error> {
+    let dialect = render.dialect;
+    if select.from_table.join.is_some() {
+        return Err(Error::GenericError(
+            "Joined tables need a table lookup, use into_sql_statement"
+                .to_string(),
+        ));
+    }
+    let projection = match &select.projection {
+        Some(projection) => {
+            projection
+                .iter()
+                .map(|expr_rename| {
+                    let expr = render_with(&expr_rename.expr, render)?;
+                    Ok(match &expr_rename.rename {
+                        Some(rename) => {
+                            format!(
+                                "{} AS {}",
+                                expr,
+                                dialect.quote_identifier(rename)
+                            )
+                        }
+                        None => expr,
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?
+                .join(", ")
+        }
+        None => "*".to_string(),
+    };
+    let mut sql = format!(
+        "SELECT {} FROM {}",
+        projection,
+        quote_qualified(&select.from_table.from.name, dialect)
+    );
+    if let Some(filter) = &select.filter {
+        sql.push_str(&format!(" WHERE {}", render_with(filter, render)?));
+    }
+    if let Some(group_by) = &select.group_by {
+        let group_by = group_by
+            .iter()
+            .map(|expr| render_with(expr, render))
+            .collect::<Result<Vec<_>, Error>>()?;
+        sql.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+    }
+    if let Some(having) = &select.having {
+        sql.push_str(&format!(" HAVING {}", render_with(having, render)?));
+    }
+    if let Some(order_by) = &select.order_by {
+        let order_by = order_by
+            .iter()
+            .map(|order| {
+                let expr = render_with(&order.expr, render)?;
+                Ok(match &order.direction {
+                    Some(direction) => {
+                        format!("{} {}", expr, sql_direction(direction))
+                    }
+                    None => expr,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        sql.push_str(&format!(" ORDER BY {}", order_by.join(", ")));
+    }
+    match &select.range {
+        Some(Range::Page(_)) if render.placeholders => {
+            sql.push_str(" LIMIT ? OFFSET ?")
+        }
+        Some(Range::Limit(limit)) if render.placeholders => {
+            sql.push_str(match limit.offset {
+                Some(_) => " LIMIT ? OFFSET ?",
+                None => " LIMIT ?",
+            })
+        }
+        Some(Range::Page(page)) => {
+            sql.push_str(&format!(
+                " LIMIT {} OFFSET {}",
+                page.page_size,
+                crate::select::page_offset(page)?
+            ));
+        }
+        Some(Range::Limit(limit)) => {
+            sql.push_str(&format!(" LIMIT {}", limit.limit));
+            if let Some(offset) = limit.offset {
+                sql.push_str(&format!(" OFFSET {}", offset));
+            }
+        }
+        None => (),
+    }
+    Ok(sql)
+}
+
+/// Render a filter as the condition of a WHERE clause, the conditions
+/// joined by `&` and `|` are grouped in a parenthesis as they were parsed,
+/// ie: `age=lt.13&student=eq.true|gender=eq.'M'`, which restq parses as
+/// `age=lt.13&(student=eq.true|gender=eq.'M')`, is
+/// `"age" < 13 AND ("student" = TRUE OR "gender" = 'M')`
+pub fn to_where_clause(
+    filter: &Expr,
+    dialect: Dialect,
+) -> Result<String, Error> {
+    render_expr(filter, dialect)
+}
+
+/// Render an expression in its sql form
+pub fn render_expr(expr: &Expr, dialect: Dialect) -> Result<String, Error> {
+    render_with(
+        expr,
+        Render {
+            dialect,
+            placeholders: false,
+        },
+    )
+}
+
+fn render_with(expr: &Expr, render: Render) -> Result<String, Error> {
+    let dialect = render.dialect;
+    match expr {
+        Expr::Column(column) => Ok(render_column(column, dialect)),
+        Expr::Value(value) if render.placeholders && *value != Value::Null => {
+            Ok("?".to_string())
+        }
+        Expr::Value(value) => Ok(render_value(value, dialect)),
+        Expr::Function(function) => {
+            let params = function
+                .params
+                .iter()
+                .map(|param| {
+                    if expr::is_wildcard(param) {
+                        Ok("*".to_string())
+                    } else {
+                        render_with(param, render)
+                    }
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Ok(format!("{}({})", function.name, params.join(", ")))
+        }
+        Expr::Nested(expr) => Ok(format!("({})", render_with(expr, render)?)),
+        Expr::BinaryOperation(binop) => render_binary_operation(binop, render),
+    }
+}
+
+fn render_value(value: &Value, dialect: Dialect) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(true) => "TRUE".to_string(),
+        Value::Bool(false) => "FALSE".to_string(),
+        Value::Number(v) => v.to_string(),
+        Value::String(v) => dialect.quote_string(v),
+    }
+}
+
+fn render_binary_operation(
+    binop: &BinaryOperation,
+    render: Render,
+) -> Result<String, Error> {
+    let dialect = render.dialect;
+    let operator = &binop.operator;
+    if !dialect.supports(operator) {
+        return Err(Error::GenericError(format!(
+            "The operator `{}` is not supported in {:?}",
+            operator, dialect
+        )));
+    }
+    if *operator == Operator::And || *operator == Operator::Or {
+        return Ok(format!(
+            "{} {} {}",
+            render_connector_side(&binop.left, operator, render)?,
+            sql_operator(operator),
+            render_connector_side(&binop.right, operator, render)?
+        ));
+    }
+    let left = render_operand(&binop.left, render)?;
+    let right = match (operator, &binop.right) {
+        (Operator::Starts, Expr::Value(Value::String(_)))
+            if render.placeholders =>
+        {
+            "?".to_string()
+        }
+        (Operator::Starts, Expr::Value(Value::String(v))) => {
+            dialect.quote_string(&format!("{}%", v))
+        }
+        (Operator::Starts, _) => {
+            return Err(Error::GenericError(
+                "starts expects a string value".to_string(),
+            ))
+        }
+        (Operator::In, right) | (Operator::NotIn, right) => {
+            format!("({})", render_with(right, render)?)
+        }
+        (_, right) => render_operand(right, render)?,
+    };
+    Ok(format!("{} {} {}", left, sql_operator(operator), right))
+}
+
+/// a side of an AND is grouped in a parenthesis when it is an OR, and the
+/// other way around, so the sql has the same grouping as the parsed filter
+fn render_connector_side(
+    expr: &Expr,
+    connector: &Operator,
+    render: Render,
+) -> Result<String, Error> {
+    let rendered = render_with(expr, render)?;
+    match expr {
+        Expr::BinaryOperation(binop)
+            if (binop.operator == Operator::And
+                || binop.operator == Operator::Or)
+                && binop.operator != *connector =>
+        {
+            Ok(format!("({})", rendered))
+        }
+        _ => Ok(rendered),
+    }
+}
+
+/// an operand of a comparison or an arithmetic, which is grouped in a
+/// parenthesis when it is itself an operation
+fn render_operand(expr: &Expr, render: Render) -> Result<String, Error> {
+    let rendered = render_with(expr, render)?;
+    match expr {
+        Expr::BinaryOperation(_) => Ok(format!("({})", rendered)),
+        _ => Ok(rendered),
+    }
+}
+
+/// The sql text of the operator, where restq's `Display` gives its keyword
+/// in the url, ie: `>=` for `gte`.
+///
+/// `starts` is an `ILIKE`, its value is rendered with a trailing `%`.
+pub fn sql_operator(operator: &Operator) -> &'static str {
+    match operator {
+        Operator::Plus => "+",
+        Operator::Minus => "-",
+        Operator::Multiply => "*",
+        Operator::Divide => "/",
+        Operator::Modulus => "%",
+        Operator::Eq => "=",
+        Operator::Neq => "!=",
+        Operator::Lt => "<",
+        Operator::Lte => "<=",
+        Operator::Gt => ">",
+        Operator::Gte => ">=",
+        Operator::And => "AND",
+        Operator::Or => "OR",
+        Operator::Like => "LIKE",
+        Operator::In => "IN",
+        Operator::NotIn => "NOT IN",
+        Operator::Is => "IS",
+        Operator::IsNot => "IS NOT",
+        Operator::Ilike | Operator::Starts => "ILIKE",
+    }
+}
+
+/// The sql text of the direction, `ASC` or `DESC`
+pub fn sql_direction(direction: &Direction) -> &'static str {
+    match direction {
+        Direction::Asc => "ASC",
+        Direction::Desc => "DESC",
+    }
+}
@@ -0,0 +1,270 @@
+//! Checks on a parsed `Select` that can't be expressed in the grammar
+use crate::{
+    expr,
+    sql::Dialect,
+};
+use restq::ast::{
+    Expr,
+    Range,
+    Select,
+    Value,
+};
+use std::collections::HashSet;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ValidationError {
+    #[error(
+        "All columns can not be selected with a group_by, specify the columns to select"
+    )]
+    WildcardWithGroupBy,
+    #[error("The query has {count} conditions, the maximum allowed is {max}")]
+    TooManyConditions { count: usize, max: usize },
+    #[error("The query has {count} joins, the maximum allowed is {max}")]
+    TooManyJoins { count: usize, max: usize },
+    #[error("The query selects {count} columns, the maximum allowed is {max}")]
+    TooManyColumns { count: usize, max: usize },
+    #[error(
+        "The query requests {requested} rows, the maximum allowed is {max}"
+    )]
+    PageSizeExceeded { requested: i64, max: i64 },
+    #[error("The query has no limit, the maximum allowed is {max} rows")]
+    Unbounded { max: i64 },
+    #[error("`{operator}` expects a list of values, found `{right}`")]
+    ScalarForList { operator: String, right: String },
+    #[error("The operator `{operator}` is not supported in {dialect:?}")]
+    UnsupportedOperator { operator: String, dialect: Dialect },
+    #[error("The number `{value}` is not a finite number")]
+    NonFiniteNumber { value: String },
+    #[error("The column `{column}` is not allowed")]
+    ColumnNotAllowed { column: String },
+}
+
+/// The maximum complexity allowed for a parsed query, since the length of
+/// the url alone is not a good measure of how costly the query is
+#[derive(Debug, Clone)]
+pub struct AstLimits {
+    /// the number of conditions in the filter and having
+    pub max_conditions: usize,
+    /// the number of tables joined to the from table
+    pub max_joins: usize,
+    /// the number of columns in the projection
+    pub max_columns: usize,
+    /// the number of rows in a page or in a limit, a query without either
+    /// is reported as `Unbounded`, since it returns every row
+    pub max_page_size: i64,
+}
+
+impl Default for AstLimits {
+    fn default() -> Self {
+        AstLimits {
+            max_conditions: 32,
+            max_joins: 4,
+            max_columns: 64,
+            max_page_size: 1000,
+        }
+    }
+}
+
+/// Check the query against the limits
+pub fn enforce_limits(
+    select: &Select,
+    limits: &AstLimits,
+) -> Result<(), ValidationError> {
+    let count = select
+        .filter
+        .as_ref()
+        .map(expr::condition_count)
+        .unwrap_or(0)
+        + select
+            .having
+            .as_ref()
+            .map(expr::condition_count)
+            .unwrap_or(0);
+    if count > limits.max_conditions {
+        return Err(ValidationError::TooManyConditions {
+            count,
+            max: limits.max_conditions,
+        });
+    }
+
+    let mut count = 0;
+    let mut from_table = &select.from_table;
+    while let Some((_, joined)) = &from_table.join {
+        count += 1;
+        from_table = joined;
+    }
+    if count > limits.max_joins {
+        return Err(ValidationError::TooManyJoins {
+            count,
+            max: limits.max_joins,
+        });
+    }
+
+    let count = select.projection.as_ref().map(Vec::len).unwrap_or(0);
+    if count > limits.max_columns {
+        return Err(ValidationError::TooManyColumns {
+            count,
+            max: limits.max_columns,
+        });
+    }
+
+    let requested = match &select.range {
+        Some(Range::Page(page)) => page.page_size,
+        Some(Range::Limit(limit)) => limit.limit,
+        None => {
+            return Err(ValidationError::Unbounded {
+                max: limits.max_page_size,
+            })
+        }
+    };
+    if requested > limits.max_page_size {
+        return Err(ValidationError::PageSizeExceeded {
+            requested,
+            max: limits.max_page_size,
+        });
+    }
+    Ok(())
+}
+
+/// A select without a projection selects all the columns, which is not
+/// valid sql when combined with a group_by.
+pub fn validate_group_by(select: &Select) -> Result<(), ValidationError> {
+    let has_group_by = select
+        .group_by
+        .as_ref()
+        .map(|group_by| !group_by.is_empty())
+        .unwrap_or(false);
+    if has_group_by && select.projection.is_none() {
+        Err(ValidationError::WildcardWithGroupBy)
+    } else {
+        Ok(())
+    }
+}
+
+/// The operators which require a list, such as `in`, must not have a single
+/// value on their right side.
+///
+/// restq has no list expression, so this currently rejects every `in` and
+/// `not_in`, which is what is wanted, since a list such as `in.(a,b)` is
+/// parsed by restq as an empty string rather than failing.
+pub fn validate_list_operands(select: &Select) -> Result<(), ValidationError> {
+    let mut result = Ok(());
+    for clause in select.filter.iter().chain(select.having.iter()) {
+        expr::walk(clause, &mut |expr| {
+            if let Expr::BinaryOperation(binop) = expr {
+                if result.is_ok() && expr::requires_list(&binop.operator) {
+                    result = Err(ValidationError::ScalarForList {
+                        operator: binop.operator.to_string(),
+                        right: binop.right.to_string(),
+                    });
+                }
+            }
+        });
+    }
+    result
+}
+
+/// Every operator used in the query must exist in the dialect, so the sql
+/// generated for it is valid, ie: `ilike` is Postgres only
+pub fn validate_operators_supported(
+    select: &Select,
+    dialect: Dialect,
+) -> Result<(), ValidationError> {
+    let mut result = Ok(());
+    crate::select::for_each_expr(select, |clause| {
+        expr::walk(clause, &mut |expr| {
+            if let Expr::BinaryOperation(binop) = expr {
+                if result.is_ok() && !dialect.supports(&binop.operator) {
+                    result = Err(ValidationError::UnsupportedOperator {
+                        operator: binop.operator.to_string(),
+                        dialect,
+                    });
+                }
+            }
+        })
+    });
+    result
+}
+
+/// Every number in the query must be finite, a `NaN` or an infinity can't
+/// be compared meaningfully. restq reads a number too large for an `f64`,
+/// ie: `1e400`, as an infinity, which the parse functions reject with this
+/// check. A query can also be built or rewritten from other sources, such as
+/// json, which this checks too.
+pub fn validate_finite_numbers(select: &Select) -> Result<(), ValidationError> {
+    let mut result = Ok(());
+    crate::select::for_each_expr(select, |clause| {
+        if result.is_ok() {
+            result = validate_finite_expr(clause);
+        }
+    });
+    result
+}
+
+/// `validate_finite_numbers` of a single expression
+pub(crate) fn validate_finite_expr(expr: &Expr) -> Result<(), ValidationError> {
+    let mut result = Ok(());
+    expr::walk(expr, &mut |expr| {
+        if let Expr::Value(value) = expr {
+            if result.is_ok() {
+                result = validate_finite_value(value);
+            }
+        }
+    });
+    result
+}
+
+pub(crate) fn validate_finite_value(
+    value: &Value,
+) -> Result<(), ValidationError> {
+    match value {
+        Value::Number(number) if !number.is_finite() => {
+            Err(ValidationError::NonFiniteNumber {
+                value: number.to_string(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Every column referred to in the query must be in the allowed columns,
+/// which are matched by their name as written, ie: `person.age`. The first
+/// column which is not allowed is reported.
+///
+/// An `order_by` entry which is the rename of a projected expression is
+/// also allowed, since it sorts on that expression. Anywhere else, as in
+/// sql, the name is the column, so `{name=>password}` doesn't allow
+/// filtering on `password`. A query without a projection selects all the
+/// columns, which this doesn't restrict, set the projection to control
+/// which columns are returned.
+pub fn validate_columns_allowed(
+    select: &Select,
+    allowed: &HashSet<String>,
+) -> Result<(), ValidationError> {
+    let renames: Vec<&String> = select
+        .projection
+        .iter()
+        .flatten()
+        .filter_map(|expr_rename| expr_rename.rename.as_ref())
+        .collect();
+    let mut checked = select.clone();
+    if let Some(order_by) = checked.order_by.as_mut() {
+        order_by.retain(|order| {
+            !matches!(&order.expr, Expr::Column(column) if renames.contains(&&column.name))
+        });
+    }
+    let mut result = Ok(());
+    crate::select::for_each_expr(&checked, |clause| {
+        expr::walk(clause, &mut |expr| {
+            if let Expr::Column(column) = expr {
+                if result.is_ok() && !allowed.contains(&column.name) {
+                    result = Err(ValidationError::ColumnNotAllowed {
+                        column: column.name.to_string(),
+                    });
+                }
+            }
+        })
+    });
+    result
+}
@@ -0,0 +1,373 @@
+//! Typed views of the string values which restq has no variant for, such as
+//! dates, timestamps, uuids, intervals and arrays.
+//!
+//! Written unquoted, ie: `created_at=gte.2023-01-01`, restq reads these as
+//! the arithmetic `2023-01-01`, so they are quoted before parsing and end up
+//! as string values which the functions here recognize.
+use crate::{
+    keywords,
+    scan::{
+        scan,
+        split_unquoted,
+        VALUE_END,
+    },
+};
+use restq::{
+    ast::Value,
+    chrono::{
+        DateTime,
+        FixedOffset,
+        NaiveDate,
+    },
+    Uuid,
+};
+
+/// The date of a string value in the ISO 8601 `2023-01-01` form
+///
+/// Example:
+/// ```rust
+///     use inquerest::{value::as_date, *};
+///
+///     let value = parse_value("2023-01-31").unwrap();
+///     assert_eq!(value, Value::String("2023-01-31".to_string()));
+///     assert_eq!(as_date(&value).unwrap().to_string(), "2023-01-31");
+///     assert_eq!(as_date(&Value::String("2023-02-31".to_string())), None);
+/// ```
+pub fn as_date(value: &Value) -> Option<NaiveDate> {
+    match value {
+        Value::String(v) => parse_date(v),
+        _ => None,
+    }
+}
+
+/// The timestamp of a string value in the RFC 3339 form, with its offset,
+/// ie: `2023-01-01T10:00:00Z` or `2023-01-01T10:00:00+02:00`
+pub fn as_datetime(value: &Value) -> Option<DateTime<FixedOffset>> {
+    match value {
+        Value::String(v) => DateTime::parse_from_rfc3339(v).ok(),
+        _ => None,
+    }
+}
+
+/// The uuid of a string value in the canonical hyphenated form, ie:
+/// `550e8400-e29b-41d4-a716-446655440000`
+///
+/// The other forms the uuid crate accepts, such as 32 hex digits without
+/// the hyphens, are not recognized, so an ordinary hex string isn't
+/// mistaken for a uuid.
+pub fn as_uuid(value: &Value) -> Option<Uuid> {
+    match value {
+        Value::String(v) => parse_uuid(v),
+        _ => None,
+    }
+}
+
+fn parse_uuid(input: &str) -> Option<Uuid> {
+    let is_canonical = input.len() == 36
+        && input.char_indices().all(|(i, ch)| {
+            match i {
+                8 | 13 | 18 | 23 => ch == '-',
+                _ => ch.is_ascii_hexdigit(),
+            }
+        });
+    if is_canonical {
+        Uuid::parse_str(input).ok()
+    } else {
+        None
+    }
+}
+
+/// A span of time, kept as the months, days and microseconds Postgres
+/// stores an interval as, since a month or a day has no fixed length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+/// The interval of a string value in the ISO 8601 duration form, ie:
+/// `PT1H30M` or `P1Y2M10DT2H`, or in the Postgres form, ie: `1 day` or
+/// `2 hours 30 minutes`, which has to be quoted since it has spaces.
+///
+/// Only the seconds, minutes and hours can have a fraction. A malformed
+/// interval is not recognized and stays an ordinary string.
+///
+/// Example:
+/// ```rust
+///     use inquerest::{value::{as_interval, Interval}, *};
+///
+///     let value = parse_value("PT1H30M").unwrap();
+///     assert_eq!(value, Value::String("PT1H30M".to_string()));
+///     assert_eq!(
+///         as_interval(&value),
+///         Some(Interval {
+///             months: 0,
+///             days: 0,
+///             microseconds: 5_400_000_000,
+///         })
+///     );
+/// ```
+pub fn as_interval(value: &Value) -> Option<Interval> {
+    match value {
+        Value::String(v) => {
+            parse_iso_interval(v).or_else(|| parse_postgres_interval(v))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Unit {
+    Year,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl Interval {
+    /// add `amount` of the unit, `None` when the amount is malformed or the
+    /// interval overflows
+    fn add(&mut self, amount: &str, unit: Unit) -> Option<()> {
+        let micros_per_unit: i64 = match unit {
+            Unit::Hour => 3_600_000_000,
+            Unit::Minute => 60_000_000,
+            Unit::Second => 1_000_000,
+            Unit::Year | Unit::Month | Unit::Week | Unit::Day => {
+                let amount: i32 = amount.parse().ok()?;
+                match unit {
+                    Unit::Year => {
+                        self.months =
+                            self.months.checked_add(amount.checked_mul(12)?)?
+                    }
+                    Unit::Month => {
+                        self.months = self.months.checked_add(amount)?
+                    }
+                    Unit::Week => {
+                        self.days =
+                            self.days.checked_add(amount.checked_mul(7)?)?
+                    }
+                    _ => self.days = self.days.checked_add(amount)?,
+                }
+                return Some(());
+            }
+        };
+        if !amount.starts_with(|ch: char| ch.is_ascii_digit() || ch == '-') {
+            return None;
+        }
+        let amount: f64 = amount.parse().ok()?;
+        let micros = (amount * micros_per_unit as f64).round();
+        if !micros.is_finite() || micros.abs() >= i64::MAX as f64 {
+            return None;
+        }
+        self.microseconds = self.microseconds.checked_add(micros as i64)?;
+        Some(())
+    }
+}
+
+/// `P[nY][nM][nW][nD][T[nH][nM][nS]]`, with the units in this order
+fn parse_iso_interval(input: &str) -> Option<Interval> {
+    let mut rest = input.strip_prefix('P')?;
+    let mut interval = Interval::default();
+    let mut in_time = false;
+    let mut last: Option<Unit> = None;
+    while !rest.is_empty() {
+        if let Some(time) = rest.strip_prefix('T') {
+            if in_time || time.is_empty() {
+                return None;
+            }
+            in_time = true;
+            rest = time;
+            continue;
+        }
+        let end = rest.find(|ch: char| ch.is_ascii_alphabetic())?;
+        let (amount, designator) = (&rest[..end], rest.as_bytes()[end]);
+        let unit = match (in_time, designator) {
+            (false, b'Y') => Unit::Year,
+            (false, b'M') => Unit::Month,
+            (false, b'W') => Unit::Week,
+            (false, b'D') => Unit::Day,
+            (true, b'H') => Unit::Hour,
+            (true, b'M') => Unit::Minute,
+            (true, b'S') => Unit::Second,
+            _ => return None,
+        };
+        if last.map(|last| last >= unit).unwrap_or(false)
+            || !amount.starts_with(|ch: char| ch.is_ascii_digit())
+        {
+            return None;
+        }
+        interval.add(amount, unit)?;
+        last = Some(unit);
+        rest = &rest[end + 1..];
+    }
+    last.map(|_| interval)
+}
+
+/// the amounts each followed by its unit, ie: `1 year 2 mons 3 days`, the
+/// `04:05:06` time form isn't recognized
+fn parse_postgres_interval(input: &str) -> Option<Interval> {
+    let mut words = input.split_whitespace();
+    let mut interval = Interval::default();
+    let mut is_empty = true;
+    while let Some(amount) = words.next() {
+        let unit = match words.next()? {
+            "year" | "years" => Unit::Year,
+            "mon" | "mons" | "month" | "months" => Unit::Month,
+            "week" | "weeks" => Unit::Week,
+            "day" | "days" => Unit::Day,
+            "hour" | "hours" => Unit::Hour,
+            "min" | "mins" | "minute" | "minutes" => Unit::Minute,
+            "sec" | "secs" | "second" | "seconds" => Unit::Second,
+            _ => return None,
+        };
+        interval.add(amount, unit)?;
+        is_empty = false;
+    }
+    if is_empty {
+        None
+    } else {
+        Some(interval)
+    }
+}
+
+/// An element of an array literal, which is a value or a nested array
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Value(Value),
+    Array(Vec<Element>),
+}
+
+/// The elements of a string value which is an array literal, in the
+/// Postgres `{red,green}` form or the `[1,2]` form, ie: as restq parses
+/// `tags=eq.{red,green}`. Arrays can be nested and `{}` is an empty array.
+///
+/// Each element is read as `parse_value` does, so an unquoted word is a
+/// string and `"a b"` is a string with a space.
+///
+/// Example:
+/// ```rust
+///     use inquerest::{value::{as_array, Element}, *};
+///
+///     let value = Value::String("{1,{2,3}}".to_string());
+///     assert_eq!(
+///         as_array(&value).unwrap(),
+///         vec![
+///             Element::Value(Value::Number(1.0)),
+///             Element::Array(vec![
+///                 Element::Value(Value::Number(2.0)),
+///                 Element::Value(Value::Number(3.0)),
+///             ]),
+///         ]
+///     );
+/// ```
+pub fn as_array(value: &Value) -> Option<Vec<Element>> {
+    match value {
+        Value::String(v) => parse_array(v),
+        _ => None,
+    }
+}
+
+fn parse_array(input: &str) -> Option<Vec<Element>> {
+    let close = match input.chars().next()? {
+        '{' => '}',
+        '[' => ']',
+        _ => return None,
+    };
+    let inner = input[1..].strip_suffix(close)?;
+    if inner.is_empty() {
+        return Some(vec![]);
+    }
+    split_elements(inner)?
+        .into_iter()
+        .map(|element| {
+            if element.is_empty() {
+                None
+            } else if element.starts_with('{') || element.starts_with('[') {
+                parse_array(element).map(Element::Array)
+            } else {
+                crate::parse_value(element).ok().map(Element::Value)
+            }
+        })
+        .collect()
+}
+
+/// the comma separated elements, leaving the commas of the nested arrays and
+/// of the quoted strings, `None` when the brackets or the quotes don't match
+fn split_elements(input: &str) -> Option<Vec<&str>> {
+    let mut closing: Vec<char> = vec![];
+    let mut scanner = scan(input);
+    for scanned in &mut scanner {
+        match scanned.ch {
+            _ if scanned.quoted => (),
+            '{' => closing.push('}'),
+            '[' => closing.push(']'),
+            '(' => closing.push(')'),
+            '}' | ']' | ')' if closing.pop() != Some(scanned.ch) => {
+                return None
+            }
+            _ => (),
+        }
+    }
+    if !closing.is_empty() || scanner.in_quote() {
+        return None;
+    }
+    Some(split_unquoted(input, ','))
+}
+
+fn parse_date(input: &str) -> Option<NaiveDate> {
+    if input.len() == 10 {
+        NaiveDate::parse_from_str(input, "%Y-%m-%d").ok()
+    } else {
+        None
+    }
+}
+
+/// the unquoted values which restq can't read as a single value
+fn is_typed_literal(input: &str) -> bool {
+    parse_date(input).is_some()
+        || DateTime::parse_from_rfc3339(input).is_ok()
+        || parse_uuid(input).is_some()
+        || parse_iso_interval(input).is_some()
+}
+
+/// Quote the dates, timestamps, uuids and ISO intervals which are the
+/// unquoted value of a condition, for restq to read them as a string
+pub(crate) fn quote_literals(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut last = 0;
+    for scanned in scan(input) {
+        let i = scanned.index;
+        if i < last || !scanned.is('.') || !follows_operator(&input[..i]) {
+            continue;
+        }
+        let start = i + 1;
+        let end = input[start..]
+            .find(|c: char| VALUE_END.contains(c))
+            .map(|p| start + p)
+            .unwrap_or_else(|| input.len());
+        let literal = &input[start..end];
+        if is_typed_literal(literal) {
+            output.push_str(&input[last..start]);
+            output.push('\'');
+            output.push_str(literal);
+            output.push('\'');
+            last = end;
+        }
+    }
+    output.push_str(&input[last..]);
+    output
+}
+
+/// whether the input ends with `=operator`, ie: the `age=lt` of `age=lt.13`
+fn follows_operator(input: &str) -> bool {
+    let operator = input
+        .rsplit(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+        .next()
+        .unwrap_or_default();
+    keywords::OPERATORS.contains(&operator)
+        && input[..input.len() - operator.len()].ends_with('=')
+}
@@ -4,7 +4,30 @@ fn test1() {
     let query = inquerest::parse_query(url);
     println!("query: {:#?}", query);
     assert_eq!(
-        "SELECT * FROM person WHERE age < 42 AND (student = true OR gender = 'M') GROUP BY sum(age), grade, gender HAVING min(age) > 42 ORDER BY age DESC, height ASC LIMIT 100 OFFSET 1900 ROWS",
+        "SELECT * FROM person WHERE age < 42 AND (student = true OR gender = 'M') GROUP BY sum(age), grade, gender HAVING min(age) > 42 ORDER BY age DESC, height ASC LIMIT 100 OFFSET 1900",
+        query.unwrap().into_sql_statement(None).unwrap().to_string()
+    );
+}
+
+#[test]
+fn function_compared_to_function() {
+    let filter = inquerest::parse_filter("min(a)=lt.max(b)");
+    assert_eq!(filter.unwrap().to_string(), "min(a)=lt.max(b)");
+
+    let url = "/person?&group_by=grade&having=min(age)=lt.max(height)";
+    let query = inquerest::parse_query(url);
+    assert_eq!(
+        "SELECT * FROM person GROUP BY grade HAVING min(age) < max(height)",
+        query.unwrap().into_sql_statement(None).unwrap().to_string()
+    );
+}
+
+#[test]
+fn function_compared_to_literal() {
+    let url = "/person?lower(name)=eq.'john'";
+    let query = inquerest::parse_query(url);
+    assert_eq!(
+        "SELECT * FROM person WHERE lower(name) = 'john'",
         query.unwrap().into_sql_statement(None).unwrap().to_string()
     );
 }
@@ -0,0 +1,43 @@
+use inquerest::parse_query;
+
+/// the displayed query doesn't include the leading `/` of the path
+fn round_trip(url: &str) {
+    let query = parse_query(url).expect("must be parsed");
+    let displayed = format!("/{}", query);
+    let reparsed = parse_query(&displayed).expect("must be reparsed");
+    assert_eq!(query, reparsed);
+}
+
+#[test]
+fn functions_in_group_by_and_having_round_trip() {
+    let url = "/person?&group_by=sum(age),grade&having=max(min(age))=gt.42";
+    let query = parse_query(url).expect("must be parsed");
+    assert_eq!(
+        query.to_string(),
+        "person&group_by=sum(age),grade&having=max(min(age))=gt.42"
+    );
+    round_trip(url);
+}
+
+#[test]
+fn queries_round_trip() {
+    let urls = [
+        "/person",
+        "/person?age=lt.42",
+        "/person?age=lt.42&student=eq.true|gender=eq.'M'",
+        "/person?(age=lt.42&student=eq.true)|gender=eq.'M'",
+        "/person?age=lt.42&(student=eq.true|gender=eq.'M')&grade=gte.5",
+        "/person{name,age}?age=lt.42",
+        "/person{name,sum(age)=>total}?&group_by=name&having=sum(age)=gt.42",
+        "/person?age=lt.42&order_by=age.desc,height.asc,name",
+        "/person?age=lt.42&page=2&page_size=10",
+        "/person?age=lt.42&limit=10",
+        "/person?age=lt.42&limit=10&offset=20",
+        "/person?&order_by=age.desc&limit=10&offset=20",
+        "/person?name=like.'%son'&deleted_at=is_not.null",
+        r#"/person?name=eq."John Doe"&height=gt.-1.5"#,
+    ];
+    for url in urls.iter() {
+        round_trip(url);
+    }
+}
@@ -0,0 +1,223 @@
+use inquerest::{
+    expr::swap_operands,
+    parse_filter,
+    Expr,
+};
+
+fn swapped(filter: &str) -> (bool, Expr) {
+    let mut expr = parse_filter(filter).expect("must be parsed");
+    let is_swapped = match &mut expr {
+        Expr::BinaryOperation(binop) => swap_operands(binop),
+        _ => panic!("expecting a binary operation"),
+    };
+    (is_swapped, expr)
+}
+
+#[test]
+fn swap_operands_flips_the_operator() {
+    let (is_swapped, expr) = swapped("13=lt.age");
+    assert!(is_swapped);
+    assert_eq!(expr, parse_filter("age=gt.13").unwrap());
+
+    let (is_swapped, expr) = swapped("13=lte.age");
+    assert!(is_swapped);
+    assert_eq!(expr, parse_filter("age=gte.13").unwrap());
+}
+
+#[test]
+fn swap_operands_keeps_symmetric_operators() {
+    let (is_swapped, expr) = swapped("13=eq.age");
+    assert!(is_swapped);
+    assert_eq!(expr, parse_filter("age=eq.13").unwrap());
+
+    let (is_swapped, expr) = swapped("13=neq.age");
+    assert!(is_swapped);
+    assert_eq!(expr, parse_filter("age=neq.13").unwrap());
+}
+
+#[test]
+fn swap_operands_leaves_unmirrored_operators() {
+    let (is_swapped, expr) = swapped("name=like.John%");
+    assert!(!is_swapped);
+    assert_eq!(expr, parse_filter("name=like.John%").unwrap());
+}
+
+#[test]
+fn function_names_are_normalized_to_lowercase() {
+    let mut upper = parse_filter("MAX(x)=gt.Sum(y)").unwrap();
+    let mut lower = parse_filter("max(x)=gt.sum(y)").unwrap();
+    assert_ne!(upper, lower);
+
+    inquerest::expr::normalize_function_names(&mut upper);
+    inquerest::expr::normalize_function_names(&mut lower);
+    assert_eq!(upper, lower);
+}
+
+#[test]
+fn or_is_distributed_over_and() {
+    use inquerest::expr::to_cnf;
+
+    let filter = parse_filter("a=eq.1|(b=eq.2&c=eq.3)").unwrap();
+    let cnf = to_cnf(&filter, 100).expect("must be converted");
+    assert_eq!(cnf.to_string(), "(a=eq.1|b=eq.2)&(a=eq.1|c=eq.3)");
+    assert_eq!(
+        cnf,
+        parse_filter("(a=eq.1|b=eq.2)&(a=eq.1|c=eq.3)").unwrap()
+    );
+
+    let filter = parse_filter("(a=eq.1&b=eq.2)|(c=eq.3&d=eq.4)").unwrap();
+    assert_eq!(
+        to_cnf(&filter, 100).expect("must be converted").to_string(),
+        "(a=eq.1|c=eq.3)&(a=eq.1|d=eq.4)&(b=eq.2|c=eq.3)&(b=eq.2|d=eq.4)"
+    );
+    assert_eq!(to_cnf(&filter, 3), None);
+}
+
+#[test]
+fn cnf_beyond_the_max_clauses_is_rejected() {
+    use inquerest::expr::to_cnf;
+
+    let filter = (0..20)
+        .map(|i| format!("(a{}=eq.1&b{}=eq.2)", i, i))
+        .collect::<Vec<_>>()
+        .join("|");
+    let filter = parse_filter(&filter).unwrap();
+    assert_eq!(to_cnf(&filter, 1_000), None);
+}
+
+#[test]
+fn cnf_of_a_cnf_filter_is_unchanged() {
+    use inquerest::expr::to_cnf;
+
+    for filter in &[
+        "a=eq.1",
+        "a=eq.1&b=eq.2",
+        "a=eq.1|b=eq.2",
+        "(a=eq.1|b=eq.2)&c=eq.3",
+    ] {
+        let filter = parse_filter(filter).unwrap();
+        assert_eq!(to_cnf(&filter, 100), Some(filter));
+    }
+}
+
+#[test]
+fn constant_arithmetic_is_folded() {
+    use inquerest::{
+        expr::fold_constants,
+        BinaryOperation,
+        Column,
+        Operator,
+        Value,
+    };
+
+    let expr = parse_filter("2+3").unwrap();
+    assert_eq!(fold_constants(&expr), Expr::Value(Value::Number(5.0)));
+
+    let expr = parse_filter("(6/2)").unwrap();
+    assert_eq!(fold_constants(&expr), Expr::Value(Value::Number(3.0)));
+
+    // price>(4-1)
+    let expr = Expr::BinaryOperation(Box::new(BinaryOperation {
+        left: Expr::Column(Column {
+            name: "price".to_string(),
+        }),
+        operator: Operator::Gt,
+        right: parse_filter("(4-1)").unwrap(),
+    }));
+    assert_eq!(fold_constants(&expr), parse_filter("price=gt.3").unwrap());
+
+    let expr = parse_filter("price*2").unwrap();
+    assert_eq!(fold_constants(&expr), expr);
+
+    let expr = parse_filter("1/0").unwrap();
+    assert_eq!(fold_constants(&expr), expr);
+}
+
+#[test]
+fn star_is_a_multiplication_between_operands() {
+    use inquerest::{
+        expr::is_wildcard,
+        Column,
+        Operator,
+        Value,
+    };
+
+    let expr = parse_filter("price*2").unwrap();
+    match &expr {
+        Expr::BinaryOperation(binop) => {
+            assert_eq!(binop.operator, Operator::Multiply);
+            assert_eq!(
+                binop.left,
+                Expr::Column(Column {
+                    name: "price".to_string()
+                })
+            );
+            assert_eq!(binop.right, Expr::Value(Value::Number(2.0)));
+        }
+        _ => panic!("expecting a multiplication, got {:?}", expr),
+    }
+    assert!(!is_wildcard(&expr));
+
+    let expr = parse_filter("count(*)=gt.1").unwrap();
+    match &expr {
+        Expr::BinaryOperation(binop) => {
+            match &binop.left {
+                Expr::Function(function) => {
+                    assert_eq!(function.params.len(), 1);
+                    assert!(is_wildcard(&function.params[0]));
+                }
+                _ => panic!("expecting count(*), got {:?}", binop.left),
+            }
+        }
+        _ => panic!("expecting a comparison, got {:?}", expr),
+    }
+
+    // a function argument is a single operand, so a `*` there is never a
+    // multiplication
+    assert!(parse_filter("count(a*b)=gt.1").is_err());
+}
+
+#[test]
+fn every_condition_is_rewritten_in_place() {
+    use inquerest::{
+        expr::conditions_mut,
+        Operator,
+    };
+
+    let mut filter =
+        parse_filter("age=lt.42&(student=eq.true|gender=eq.'M')").unwrap();
+    for condition in conditions_mut(&mut filter) {
+        condition.operator = match condition.operator {
+            Operator::Lt => Operator::Gte,
+            Operator::Eq => Operator::Neq,
+            _ => panic!("unexpected operator {:?}", condition.operator),
+        };
+    }
+    assert_eq!(
+        filter,
+        parse_filter("age=gte.42&(student=neq.true|gender=neq.'M')").unwrap()
+    );
+}
+
+#[test]
+fn dotted_and_infix_forms_of_the_same_filter() {
+    use inquerest::expr::Infix;
+
+    let filter = parse_filter("age=lt.13&(student=eq.true|gender=eq.'M')")
+        .expect("must be parsed");
+    assert_eq!(
+        filter.to_string(),
+        "age=lt.13&(student=eq.true|gender=eq.'M')"
+    );
+    assert_eq!(
+        Infix(&filter).to_string(),
+        "age < 13 AND (student = true OR gender = 'M')"
+    );
+
+    let filter = parse_filter("min(age)=gte.-1.5|name=is_not.null")
+        .expect("must be parsed");
+    assert_eq!(
+        Infix(&filter).to_string(),
+        "min(age) >= -1.5 OR name IS NOT null"
+    );
+}
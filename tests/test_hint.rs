@@ -0,0 +1,38 @@
+use inquerest::hint::{
+    explain_plan_hints,
+    Hint,
+};
+
+#[test]
+fn hints_for_a_leading_wildcard_and_an_or_across_columns() {
+    let query = inquerest::parse_query(
+        "/person?name=like.%son&(student=eq.true|gender=eq.'M')",
+    )
+    .expect("must be parsed");
+    let hints = explain_plan_hints(&query);
+    assert_eq!(
+        hints,
+        vec![
+            Hint::LeadingWildcard {
+                column: "name".to_string()
+            },
+            Hint::OrAcrossColumns {
+                columns: vec!["student".to_string(), "gender".to_string()]
+            },
+            Hint::Unbounded,
+        ]
+    );
+    assert_eq!(
+        hints[0].to_string(),
+        "leading-wildcard like on `name` can't use a btree index"
+    );
+}
+
+#[test]
+fn no_hints_for_a_bounded_query_on_one_column() {
+    let query = inquerest::parse_query(
+        "/person?name=like.'son%'&(age=lt.13|age=gt.65)&limit=10",
+    )
+    .expect("must be parsed");
+    assert_eq!(explain_plan_hints(&query), vec![]);
+}
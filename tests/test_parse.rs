@@ -0,0 +1,927 @@
+#[test]
+fn deeply_nested_functions_are_rejected() {
+    let depth = 10_000;
+    let filter = format!("{}age{}=eq.1", "f(".repeat(depth), ")".repeat(depth));
+    let result = inquerest::parse_filter(&filter);
+    assert!(result.is_err());
+
+    let url = format!("/person?{}", filter);
+    let result = inquerest::parse_query(&url);
+    assert!(result.is_err());
+}
+
+#[test]
+fn nested_functions_within_the_limit_are_parsed() {
+    let filter = "max(min(age))=eq.1";
+    let result = inquerest::parse_filter(filter);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn brackets_in_quoted_strings_are_not_nesting() {
+    let brackets = "(".repeat(inquerest::MAX_NESTING_DEPTH + 1);
+    let filter = format!("name=eq.'{}'", brackets);
+    assert!(inquerest::parse_filter(&filter).is_ok());
+
+    let query = inquerest::parse_query(&format!("/person?{}", filter))
+        .expect("must be parsed");
+    assert_eq!(query.to_string(), format!("person?{}", filter));
+}
+
+#[test]
+fn numbers_too_large_for_an_f64_are_an_error() {
+    assert!(inquerest::parse_query("/person?age=lt.1e400").is_err());
+    assert!(inquerest::parse_query("/person{1e400}").is_err());
+    assert!(inquerest::parse_filter("age=gt.-1e400").is_err());
+    assert!(inquerest::parse_value("1e400").is_err());
+    assert!(inquerest::parse_value("1e300").is_ok());
+}
+
+#[test]
+fn page_size_defaults_when_only_page_is_given() {
+    let query = inquerest::parse_query("/person?age=lt.42&page=2")
+        .expect("must be parsed");
+    let expected =
+        inquerest::parse_query("/person?age=lt.42&page=2&page_size=20")
+            .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn page_defaults_when_only_page_size_is_given() {
+    let query =
+        inquerest::parse_query("/person?page_size=10").expect("must be parsed");
+    let expected = inquerest::parse_query("/person?&page=1&page_size=10")
+        .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn default_page_size_is_configurable() {
+    let options = inquerest::ParseOptions {
+        default_page_size: 50,
+        ..Default::default()
+    };
+    let query = inquerest::parse_query_with(
+        "/person?order_by=age.desc&page=3",
+        &options,
+    )
+    .expect("must be parsed");
+    let expected = inquerest::parse_query(
+        "/person?&order_by=age.desc&page=3&page_size=50",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn direction_round_trips() {
+    use inquerest::Direction;
+
+    for direction in [Direction::Asc, Direction::Desc] {
+        let parsed = inquerest::parse_direction(&direction.to_string())
+            .expect("must be parsed");
+        assert_eq!(parsed, direction);
+    }
+    assert!(inquerest::parse_direction("descending").is_err());
+}
+
+#[test]
+fn empty_input_is_an_empty_query() {
+    use inquerest::Select;
+
+    assert_eq!(inquerest::parse_query("").unwrap(), Select::default());
+    assert_eq!(inquerest::parse_query("  \n").unwrap(), Select::default());
+}
+
+#[test]
+fn semicolon_separated_parameters() {
+    let options = inquerest::ParseOptions {
+        separator: ';',
+        ..Default::default()
+    };
+    let query = inquerest::parse_query_with(
+        "/person?age=lt.42&student=eq.true;(gender=eq.'M'|gender=eq.'F');order_by=age.desc;page=2;page_size=10",
+        &options,
+    )
+    .expect("must be parsed");
+    let expected = inquerest::parse_query(
+        "/person?age=lt.42&student=eq.true&(gender=eq.'M'|gender=eq.'F')&order_by=age.desc&page=2&page_size=10",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn range_header_style_is_limit_and_offset() {
+    let query = inquerest::parse_query("/person?age=lt.42&range=0-24")
+        .expect("must be parsed");
+    let expected =
+        inquerest::parse_query("/person?age=lt.42&limit=25&offset=0")
+            .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    let query =
+        inquerest::parse_query("/person?range=25-49").expect("must be parsed");
+    let expected = inquerest::parse_query("/person?&limit=25&offset=25")
+        .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn invalid_range_is_an_error() {
+    assert!(inquerest::parse_query("/person?range=24-0").is_err());
+    assert!(inquerest::parse_query("/person?range=0").is_err());
+    assert!(inquerest::parse_query("/person?range=a-b").is_err());
+    assert!(
+        inquerest::parse_query("/person?range=0-9223372036854775807").is_err()
+    );
+    assert!(
+        inquerest::parse_query("/person?range=1-9223372036854775807").is_ok()
+    );
+}
+
+#[test]
+fn connector_round_trips() {
+    use inquerest::Operator;
+
+    for connector in [Operator::And, Operator::Or] {
+        let parsed = inquerest::parse_connector(&connector.to_string())
+            .expect("must be parsed");
+        assert_eq!(parsed, connector);
+    }
+    assert_eq!(inquerest::parse_connector("or").unwrap(), Operator::Or);
+    assert!(inquerest::parse_connector("eq").is_err());
+}
+
+#[test]
+fn connector_inside_function_arguments_is_not_split() {
+    use inquerest::{
+        BinaryOperation,
+        Column,
+        Expr,
+        Function,
+        Operator,
+        Value,
+    };
+
+    let filter = inquerest::parse_filter(r#"coalesce(name,"a & b")=eq.'x'"#)
+        .expect("must be parsed");
+    assert_eq!(
+        filter,
+        Expr::BinaryOperation(Box::new(BinaryOperation {
+            left: Expr::Function(Function {
+                name: "coalesce".to_string(),
+                params: vec![
+                    Expr::Column(Column {
+                        name: "name".to_string()
+                    }),
+                    Expr::Value(Value::String("a & b".to_string())),
+                ],
+            }),
+            operator: Operator::Eq,
+            right: Expr::Value(Value::String("x".to_string())),
+        }))
+    );
+
+    // the parameters are split before parsing, which must also respect the
+    // quotes and parenthesis
+    let query = inquerest::parse_query(
+        r#"/person?coalesce(name,"a & b")=eq.'x'&page=2"#,
+    )
+    .expect("must be parsed");
+    assert_eq!(query.filter, Some(filter));
+}
+
+#[test]
+fn query_from_pairs() {
+    let pairs = [
+        ("age", "lt.42"),
+        ("(student", "eq.true|gender=eq.'M')"),
+        ("group_by", "grade"),
+        ("order_by", "age.desc"),
+        ("order_by", "height.asc"),
+        ("page", "2"),
+    ];
+    let query =
+        inquerest::parse_pairs("/person", &pairs).expect("must be parsed");
+    let expected = inquerest::parse_query(
+        "/person?age=lt.42&(student=eq.true|gender=eq.'M')&group_by=grade&order_by=age.desc,height.asc&page=2&page_size=20",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn first_is_a_limit_from_the_start() {
+    let query = inquerest::parse_query("/person?age=lt.42&first=10")
+        .expect("must be parsed");
+    let expected =
+        inquerest::parse_query("/person?age=lt.42&limit=10&offset=0")
+            .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn last_reverses_the_order() {
+    let query = inquerest::parse_query(
+        "/person?age=lt.42&order_by=age.desc,name&last=10",
+    )
+    .expect("must be parsed");
+    let expected = inquerest::parse_query(
+        "/person?age=lt.42&order_by=age.asc,name.desc&limit=10&offset=0",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    assert!(inquerest::parse_query("/person?age=lt.42&last=10").is_err());
+    assert!(inquerest::parse_query("/person?first=1&last=1").is_err());
+    assert!(inquerest::parse_query("/person?first=-1").is_err());
+}
+
+#[test]
+fn lenient_booleans_for_the_given_columns() {
+    use inquerest::ParseOptions;
+
+    let options = ParseOptions {
+        lenient_bool_columns: vec!["active".to_string()],
+        ..Default::default()
+    };
+    let expected = inquerest::parse_query(
+        "/person?active=eq.true&age=eq.1&(active=eq.false|active=neq.true)",
+    )
+    .expect("must be parsed");
+    let query = inquerest::parse_query_with(
+        "/person?active=eq.yes&age=eq.1&(active=eq.0|active=neq.on)",
+        &options,
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    let query = inquerest::parse_query_with(
+        "/person?active=eq.1&age=eq.1&(active=eq.off|active=neq.1)",
+        &options,
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    // without the option, the values are left as they are parsed
+    let query =
+        inquerest::parse_query("/person?active=eq.1").expect("must be parsed");
+    assert_eq!(query.to_string(), "person?active=eq.1");
+}
+
+#[test]
+fn json_aggregation_functions_in_the_projection() {
+    use inquerest::{
+        restq::ast::ExprRename,
+        Column,
+        Expr,
+        Function,
+        Value,
+    };
+
+    let query = inquerest::parse_query(
+        "/orders{customer_id,json_agg(orders)=>data}?&group_by=customer_id",
+    )
+    .expect("must be parsed");
+    let projection = query.projection.expect("must have a projection");
+    assert_eq!(
+        projection[1],
+        ExprRename {
+            expr: Expr::Function(Function {
+                name: "json_agg".to_string(),
+                params: vec![Expr::Column(Column {
+                    name: "orders".to_string()
+                })],
+            }),
+            rename: Some("data".to_string()),
+        }
+    );
+
+    let query =
+        inquerest::parse_query("/person{jsonb_build_object('id',id)=>data}")
+            .expect("must be parsed");
+    let projection = query.projection.expect("must have a projection");
+    assert_eq!(
+        projection[0].expr,
+        Expr::Function(Function {
+            name: "jsonb_build_object".to_string(),
+            params: vec![
+                Expr::Value(Value::String("id".to_string())),
+                Expr::Column(Column {
+                    name: "id".to_string()
+                }),
+            ],
+        })
+    );
+}
+
+#[test]
+fn negative_numbers_after_the_operator() {
+    use inquerest::{
+        BinaryOperation,
+        Column,
+        Expr,
+        Operator,
+        Value,
+    };
+
+    let filter =
+        inquerest::parse_filter("temperature=lt.-5").expect("must be parsed");
+    assert_eq!(
+        filter,
+        Expr::BinaryOperation(Box::new(BinaryOperation {
+            left: Expr::Column(Column {
+                name: "temperature".to_string()
+            }),
+            operator: Operator::Lt,
+            right: Expr::Value(Value::Number(-5.0)),
+        }))
+    );
+
+    // the minus is not mistaken for a connector or a subtraction
+    let filter = inquerest::parse_filter("temperature=lt.-5|humidity=gt.-10")
+        .expect("must be parsed");
+    assert_eq!(filter.to_string(), "temperature=lt.-5|humidity=gt.-10");
+}
+
+#[test]
+fn decimal_numbers_and_qualified_columns() {
+    use inquerest::{
+        BinaryOperation,
+        Column,
+        Expr,
+        Operator,
+        Value,
+    };
+
+    let filter =
+        inquerest::parse_filter("price=gte.19.99").expect("must be parsed");
+    assert_eq!(
+        filter,
+        Expr::BinaryOperation(Box::new(BinaryOperation {
+            left: Expr::Column(Column {
+                name: "price".to_string()
+            }),
+            operator: Operator::Gte,
+            right: Expr::Value(Value::Number(19.99)),
+        }))
+    );
+
+    // the dot of a qualified column is not a decimal point
+    let filter =
+        inquerest::parse_filter("person.age=gt.-0.5").expect("must be parsed");
+    assert_eq!(
+        filter,
+        Expr::BinaryOperation(Box::new(BinaryOperation {
+            left: Expr::Column(Column {
+                name: "person.age".to_string()
+            }),
+            operator: Operator::Gt,
+            right: Expr::Value(Value::Number(-0.5)),
+        }))
+    );
+}
+
+#[test]
+fn is_null_condition() {
+    use inquerest::{
+        BinaryOperation,
+        Column,
+        Expr,
+        Operator,
+        Value,
+    };
+
+    let filter =
+        inquerest::parse_filter("deleted_at=is.null").expect("must be parsed");
+    assert_eq!(
+        filter,
+        Expr::BinaryOperation(Box::new(BinaryOperation {
+            left: Expr::Column(Column {
+                name: "deleted_at".to_string()
+            }),
+            operator: Operator::Is,
+            right: Expr::Value(Value::Null),
+        }))
+    );
+    assert_eq!(filter.to_string(), "deleted_at=is.null");
+
+    let query = inquerest::parse_query(
+        "/person?age=lt.42&(deleted_at=is.null|active=is.true)",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        query.to_string(),
+        "person?age=lt.42&(deleted_at=is.null|active=is.true)"
+    );
+    assert_eq!(
+        inquerest::select::bind_values(&query),
+        vec![&Value::Number(42.0), &Value::Bool(true)]
+    );
+
+    // only the conditions written with `=is.` are an is condition, the column
+    // `is.null` of `eq.is.null` stays a column, as does one named like the
+    // marker of the rewrite
+    let filter = inquerest::parse_filter("deleted_at=eq.is.null|x=eq._m_null")
+        .expect("must be parsed");
+    assert_eq!(filter.to_string(), "deleted_at=eq.is.null|x=eq._m_null");
+    let query =
+        inquerest::parse_query("/person?deleted_at=eq.is.null&_m_x=is.false")
+            .expect("must be parsed");
+    assert_eq!(
+        query.to_string(),
+        "person?deleted_at=eq.is.null&_m_x=is.false"
+    );
+}
+
+#[test]
+fn single_values() {
+    use inquerest::{
+        parse_value,
+        Value,
+    };
+
+    assert_eq!(parse_value("null").unwrap(), Value::Null);
+    assert_eq!(parse_value("true").unwrap(), Value::Bool(true));
+    assert_eq!(parse_value("false").unwrap(), Value::Bool(false));
+    assert_eq!(parse_value("42").unwrap(), Value::Number(42.0));
+    assert_eq!(parse_value("-0.5").unwrap(), Value::Number(-0.5));
+    assert_eq!(
+        parse_value(r#""John Doe""#).unwrap(),
+        Value::String("John Doe".to_string())
+    );
+    assert_eq!(parse_value("'M'").unwrap(), Value::String("M".to_string()));
+    assert_eq!(
+        parse_value("active").unwrap(),
+        Value::String("active".to_string())
+    );
+
+    assert!(parse_value("42 junk").is_err());
+    assert!(parse_value("42&age=lt.13").is_err());
+    assert!(parse_value("min(age)").is_err());
+}
+
+#[test]
+fn quoted_string_values() {
+    use inquerest::{
+        Expr,
+        Value,
+    };
+
+    let right = |filter: &str| {
+        match inquerest::parse_filter(filter).expect("must be parsed") {
+            Expr::BinaryOperation(binop) => binop.right,
+            _ => panic!("expecting a condition"),
+        }
+    };
+    assert_eq!(
+        right(r#"name=eq."John Doe""#),
+        Expr::Value(Value::String("John Doe".to_string()))
+    );
+    assert_eq!(
+        right(r#"name=eq."say \"hi\"""#),
+        Expr::Value(Value::String(r#"say "hi""#.to_string()))
+    );
+    // an unquoted word is still a column
+    assert!(matches!(right("name=eq.nickname"), Expr::Column(_)));
+}
+
+#[test]
+fn global_direction_applies_to_entries_without_one() {
+    let query = inquerest::parse_query(
+        "/person?age=lt.42&order_by=name,age.asc&dir=desc",
+    )
+    .expect("must be parsed");
+    let expected =
+        inquerest::parse_query("/person?age=lt.42&order_by=name.desc,age.asc")
+            .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    assert!(inquerest::parse_query("/person?order_by=name&dir=up").is_err());
+}
+
+#[test]
+fn functions_with_several_parameters() {
+    use inquerest::{
+        Column,
+        Expr,
+        Function,
+        Value,
+    };
+
+    let left = |filter: &str| {
+        match inquerest::parse_filter(filter).expect("must be parsed") {
+            Expr::BinaryOperation(binop) => binop.left,
+            _ => panic!("expecting a condition"),
+        }
+    };
+    assert_eq!(
+        left("coalesce(a,b)=eq.1"),
+        Expr::Function(Function {
+            name: "coalesce".to_string(),
+            params: vec![
+                Expr::Column(Column {
+                    name: "a".to_string()
+                }),
+                Expr::Column(Column {
+                    name: "b".to_string()
+                }),
+            ],
+        })
+    );
+
+    // `now()` has no arguments, unlike `now('')`
+    assert_eq!(
+        left("now()=gt.created"),
+        Expr::Function(Function {
+            name: "now".to_string(),
+            params: vec![],
+        })
+    );
+    assert_eq!(
+        left("now('')=gt.created"),
+        Expr::Function(Function {
+            name: "now".to_string(),
+            params: vec![Expr::Value(Value::String("".to_string()))],
+        })
+    );
+    assert_eq!(inquerest::parse_function("now()").unwrap().params, vec![]);
+    let query = inquerest::parse_query(
+        "/person{name,now()}?created=lt.now()&order_by=random()",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        query.to_string(),
+        "person(name,now())?created=lt.now()&order_by=random()"
+    );
+    assert_eq!(
+        inquerest::sql::to_sql(&query, inquerest::sql::Dialect::Postgres)
+            .unwrap(),
+        "SELECT \"name\", now() FROM \"person\" WHERE \"created\" < now() ORDER BY random()"
+    );
+}
+
+#[test]
+fn single_function_calls() {
+    use inquerest::{
+        parse_function,
+        Column,
+        Expr,
+        Function,
+        Value,
+    };
+
+    let column = |name: &str| {
+        Expr::Column(Column {
+            name: name.to_string(),
+        })
+    };
+    assert_eq!(
+        parse_function("min(age)").unwrap(),
+        Function {
+            name: "min".to_string(),
+            params: vec![column("age")],
+        }
+    );
+    assert_eq!(
+        parse_function("coalesce(a, b, 'c d')").unwrap(),
+        Function {
+            name: "coalesce".to_string(),
+            params: vec![
+                column("a"),
+                column("b"),
+                Expr::Value(Value::String("c d".to_string()))
+            ],
+        }
+    );
+    assert!(parse_function("age").is_err());
+    assert!(parse_function("min(age)&max(age)").is_err());
+}
+
+#[test]
+fn bracket_filter_with_infix_operators() {
+    use inquerest::{
+        parse_bracket_filter,
+        parse_filter,
+    };
+
+    let filter =
+        parse_bracket_filter("[age>=13][age<=19][grade!=5][name='a<b']")
+            .expect("must be parsed");
+    let expected =
+        parse_filter("age=gte.13&age=lte.19&grade=neq.5&name=eq.'a<b'")
+            .unwrap();
+    assert_eq!(filter, expected);
+
+    let filter = parse_bracket_filter("[age<13] [height>1.5][student=true]")
+        .expect("must be parsed");
+    let expected =
+        parse_filter("age=lt.13&height=gt.1.5&student=eq.true").unwrap();
+    assert_eq!(filter, expected);
+
+    assert!(parse_bracket_filter("").is_err());
+    assert!(parse_bracket_filter("[age<13").is_err());
+    assert!(parse_bracket_filter("[age]").is_err());
+    assert!(parse_bracket_filter("[name=x y]").is_err());
+    assert!(parse_bracket_filter("[age<>1]").is_err());
+    assert!(parse_bracket_filter("[age=>1]").is_err());
+    for filter in &[
+        "[age<13|admin=eq.true]",
+        "[age<13&admin=eq.true]",
+        "[age<13)|(admin=true]",
+        "[age<(13)]",
+    ] {
+        assert!(parse_bracket_filter(filter).is_err(), "{}", filter);
+    }
+    assert_eq!(
+        parse_bracket_filter("[name='a|b&(c)']").unwrap(),
+        parse_filter("name=eq.'a|b&(c)'").unwrap()
+    );
+    assert!(parse_bracket_filter("age<13").is_err());
+    assert!(parse_bracket_filter("[<13]").is_err());
+}
+
+#[test]
+fn fields_is_the_projection() {
+    let expected =
+        inquerest::parse_query("/person{name,age}?age=lt.42&order_by=age")
+            .expect("must be parsed");
+    let query = inquerest::parse_query(
+        "/person?fields=name,age&age=lt.42&order_by=age",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    let query = inquerest::parse_query(
+        "/person?age=lt.42&fields[person]=name,age&order_by=age",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    assert!(inquerest::parse_query("/person?fields[orders]=total").is_err());
+    assert!(inquerest::parse_query("/person{name}?fields=age").is_err());
+}
+
+#[test]
+fn select_is_the_projection() {
+    use inquerest::{
+        restq::ast::ExprRename,
+        Column,
+        Expr,
+        Function,
+    };
+
+    let query = inquerest::parse_query(
+        "/person?select=name,years:age,sum(score)&age=lt.42&group_by=name,age",
+    )
+    .expect("must be parsed");
+    let projection = query.projection.expect("must have a projection");
+    assert_eq!(
+        projection,
+        vec![
+            ExprRename {
+                expr: Expr::Column(Column {
+                    name: "name".into()
+                }),
+                rename: None,
+            },
+            ExprRename {
+                expr: Expr::Column(Column { name: "age".into() }),
+                rename: Some("years".into()),
+            },
+            ExprRename {
+                expr: Expr::Function(Function {
+                    name: "sum".into(),
+                    params: vec![Expr::Column(Column {
+                        name: "score".into()
+                    })],
+                }),
+                rename: None,
+            },
+        ]
+    );
+
+    let query = inquerest::parse_query("/person?select=*&age=lt.42")
+        .expect("must be parsed");
+    assert_eq!(query.projection, None);
+    assert!(inquerest::parse_query("/person{name}?select=age").is_err());
+}
+
+#[test]
+fn literal_values_in_the_projection() {
+    use inquerest::{
+        restq::ast::ExprRename,
+        Column,
+        Expr,
+        Value,
+    };
+
+    let expected = vec![
+        ExprRename {
+            expr: Expr::Value(Value::Number(1.0)),
+            rename: Some("one".into()),
+        },
+        ExprRename {
+            expr: Expr::Value(Value::String("hi".into())),
+            rename: Some("greeting".into()),
+        },
+        ExprRename {
+            expr: Expr::Column(Column {
+                name: "name".into(),
+            }),
+            rename: None,
+        },
+    ];
+    let query = inquerest::parse_query("/person{1=>one,'hi'=>greeting,name}")
+        .expect("must be parsed");
+    assert_eq!(query.projection.as_ref(), Some(&expected));
+
+    let query =
+        inquerest::parse_query("/person?select=1:one,\"hi\":greeting,name")
+            .expect("must be parsed");
+    assert_eq!(query.projection.as_ref(), Some(&expected));
+
+    let query =
+        inquerest::parse_query("/person?select=one:1,greeting:'hi',name")
+            .expect("must be parsed");
+    assert_eq!(query.projection, Some(expected));
+}
+
+#[test]
+fn empty_value_policies() {
+    use inquerest::{
+        EmptyValue,
+        ParseOptions,
+    };
+
+    let parse = |url: &str, empty_value: EmptyValue| {
+        let options = ParseOptions {
+            empty_value,
+            ..Default::default()
+        };
+        inquerest::parse_query_with(url, &options)
+    };
+
+    let query = parse("/person?name=eq.&age=lt.13", EmptyValue::String)
+        .expect("must be parsed");
+    assert_eq!(query.to_string(), "person?name=eq.''&age=lt.13");
+
+    let query = parse(
+        "/person?name=eq.&(age=lt.13|nickname=neq.)",
+        EmptyValue::Null,
+    )
+    .expect("must be parsed");
+    let expected = inquerest::parse_query(
+        "/person?name=is.null&(age=lt.13|nickname=is_not.null)",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+    assert!(parse("/person?age=lt.", EmptyValue::Null).is_err());
+
+    assert!(parse("/person?name=eq.&age=lt.13", EmptyValue::Error).is_err());
+    // a dot inside a quoted value is not a missing value
+    let query = parse("/person?name=eq.'eq.'&age=lt.13", EmptyValue::Error)
+        .expect("must be parsed");
+    assert_eq!(query.to_string(), "person?name=eq.'eq.'&age=lt.13");
+}
+
+#[test]
+fn renamed_and_plain_items_in_the_same_projection() {
+    use inquerest::{
+        restq::ast::ExprRename,
+        Column,
+        Expr,
+        Function,
+    };
+
+    let query = inquerest::parse_query("/person{sum(amount)=>total,name}")
+        .expect("must be parsed");
+    assert_eq!(
+        query.projection,
+        Some(vec![
+            ExprRename {
+                expr: Expr::Function(Function {
+                    name: "sum".to_string(),
+                    params: vec![Expr::Column(Column {
+                        name: "amount".to_string()
+                    })],
+                }),
+                rename: Some("total".to_string()),
+            },
+            ExprRename {
+                expr: Expr::Column(Column {
+                    name: "name".to_string()
+                }),
+                rename: None,
+            },
+        ])
+    );
+}
+
+#[test]
+fn keywords_are_case_insensitive() {
+    let query =
+        inquerest::parse_query("/person?AGE=EQ.13&student=Eq.TRUE&deleted=IS.Null&order_by=age.DESC,name.Asc")
+            .expect("must be parsed");
+    let expected = inquerest::parse_query(
+        "/person?AGE=eq.13&student=eq.true&deleted=is.null&order_by=age.desc,name.asc",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    assert_eq!(
+        inquerest::parse_filter("age=GTE.13|name=LIKE.'J%'").unwrap(),
+        inquerest::parse_filter("age=gte.13|name=like.'J%'").unwrap()
+    );
+    assert_eq!(
+        inquerest::parse_direction("Desc").unwrap(),
+        inquerest::Direction::Desc
+    );
+
+    // the column names and the string values keep their case
+    let query = inquerest::parse_query(
+        "/person?Grade=eq.DESC&name=eq.'TRUE'&order_by=Name.desc",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        query.to_string(),
+        "person?Grade=eq.DESC&name=eq.'TRUE'&order_by=Name.desc"
+    );
+}
+
+#[test]
+fn parse_errors_have_a_position() {
+    let error = inquerest::parse_query("/person?age=lt.13&(grade=eq.2")
+        .expect_err("the group is not closed");
+    assert_eq!(error.position, Some(18));
+    assert!(error.message.contains("found: ("), "{}", error.message);
+
+    let error = inquerest::parse_query("/person?age=lt.13&page=x").unwrap_err();
+    assert_eq!(error.position, Some(17));
+
+    let error = inquerest::parse_query("/person?range=10").unwrap_err();
+    assert_eq!(error.position, None);
+    assert!(error.message.starts_with("Invalid range"));
+}
+
+#[test]
+fn parse_error_positions_are_in_the_input() {
+    // `page` and `page_size` are left where they are when both are given
+    let error = inquerest::parse_query(
+        "/person?page=2&page_size=10&age=lt.13&(grade=eq.2",
+    )
+    .unwrap_err();
+    assert_eq!(error.position, Some(27));
+    let error = inquerest::parse_query(
+        "/person?age=lt.13&(grade=eq.2&page=2&page_size=10",
+    )
+    .unwrap_err();
+    assert_eq!(error.position, Some(18));
+
+    // a lone `page` is moved to the end, with the default `page_size`
+    let error = inquerest::parse_query("/person?page=2&age=lt.13&(grade=eq.2")
+        .unwrap_err();
+    assert_eq!(error.position, Some(25));
+
+    // the quotes added around a date and the lowercased keywords
+    let error = inquerest::parse_query(
+        "/orders?CREATED=GTE.2023-01-01&deleted=is.null&(total=gt.1",
+    )
+    .unwrap_err();
+    assert_eq!(error.position, Some(47));
+    assert!(error.message.ends_with("found: ("), "{}", error.message);
+    assert!(!error.message.contains(" at "), "{}", error.message);
+    // and the markers of a function without arguments
+    let error = inquerest::parse_query(
+        "/person{name,now()}?created=lt.now()&(total=gt.1",
+    )
+    .unwrap_err();
+    assert_eq!(error.position, Some(37));
+}
+
+#[test]
+fn every_quote_restq_reads_is_skipped_by_the_rewrites() {
+    let query = inquerest::parse_query("/person?select=`a,b`:label,name")
+        .expect("must be parsed");
+    assert_eq!(query.to_string(), "person('a,b'=>label,name)");
+
+    let query = inquerest::parse_query("/person?name=eq.`x&y`&age=lt.13")
+        .expect("must be parsed");
+    assert_eq!(query.to_string(), "person?name=eq.'x&y'&age=lt.13");
+
+    let options = inquerest::ParseOptions {
+        separator: ';',
+        ..Default::default()
+    };
+    let query = inquerest::parse_query_with(
+        "/person?created_at=gte.2023-01-01;age=LT.13",
+        &options,
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        query.to_string(),
+        "person?created_at=gte.'2023-01-01'&age=lt.13"
+    );
+}
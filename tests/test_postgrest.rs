@@ -0,0 +1,69 @@
+use inquerest::{
+    parse_query,
+    postgrest::to_postgrest_string,
+};
+
+#[test]
+fn filters_select_and_order_in_postgrest_form() {
+    let query = parse_query(
+        "/person{name,age=>years}?age=lt.13&(student=eq.true|gender=eq.'M')&order_by=age.desc,height.asc&limit=10&offset=20",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        to_postgrest_string(&query).unwrap(),
+        "/person?select=name,years:age&age=lt.13&or=(student.eq.true,gender.eq.M)&order=age.desc,height.asc&limit=10&offset=20"
+    );
+}
+
+#[test]
+fn nested_logic_and_patterns_in_postgrest_form() {
+    let query = parse_query(
+        "/person?name=like.'John%'&(grade=gte.3|(student=eq.true&city=eq.'New York'))&page=3&page_size=10",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        to_postgrest_string(&query).unwrap(),
+        "/person?name=like.John*&or=(grade.gte.3,and(student.eq.true,city.eq.\"New%20York\"))&limit=10&offset=20"
+    );
+}
+
+#[test]
+fn unsupported_features_are_an_error() {
+    let query = parse_query("/person?&group_by=grade").expect("must be parsed");
+    assert!(to_postgrest_string(&query).is_err());
+
+    let query = parse_query("/person<-users").expect("must be parsed");
+    assert!(to_postgrest_string(&query).is_err());
+
+    let query = parse_query("/person?min(age)=gt.3").expect("must be parsed");
+    assert!(to_postgrest_string(&query).is_err());
+}
+
+#[test]
+fn reserved_characters_are_percent_encoded() {
+    let query = parse_query("/person?name=eq.'x&role=eq.admin'|name=eq.'a+b'")
+        .expect("must be parsed");
+    assert_eq!(
+        to_postgrest_string(&query).unwrap(),
+        "/person?or=(name.eq.\"x%26role%3Deq.admin\",name.eq.a%2Bb)"
+    );
+
+    let query =
+        parse_query("/person?name=eq.'x&role=eq.admin'&note=eq.'100% #1'")
+            .expect("must be parsed");
+    assert_eq!(
+        to_postgrest_string(&query).unwrap(),
+        "/person?name=eq.x%26role%3Deq.admin&note=eq.100%25%20%231"
+    );
+}
+
+#[test]
+fn overflowing_page_is_an_error() {
+    let query = parse_query(&format!("/person?page={}&page_size=10", i64::MAX))
+        .expect("must be parsed");
+    assert!(to_postgrest_string(&query).is_err());
+
+    let query =
+        parse_query("/person?page=0&page_size=10").expect("must be parsed");
+    assert!(to_postgrest_string(&query).is_err());
+}
@@ -0,0 +1,234 @@
+//! Generates random queries, displays them and parses them back, so the
+//! printer and the parser can't drift apart.
+//!
+//! The generated queries are in the shape the parser produces: the
+//! connectors are right associative, so chains lean to the right.
+use inquerest::{
+    restq::ast::{
+        ExprRename,
+        FromTable,
+        Limit,
+        Page,
+        Range,
+        Table,
+    },
+    BinaryOperation,
+    Column,
+    Direction,
+    Expr,
+    Function,
+    Operator,
+    Order,
+    Select,
+    Value,
+};
+
+/// a small xorshift generator, so the test is deterministic
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self) -> bool {
+        self.below(2) == 0
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+const COLUMNS: &[&str] = &["age", "name", "person.grade", "is_active"];
+
+fn column(rng: &mut Rng) -> Expr {
+    Expr::Column(Column {
+        name: rng.pick(COLUMNS).to_string(),
+    })
+}
+
+fn value(rng: &mut Rng) -> Value {
+    match rng.below(4) {
+        0 => Value::Bool(rng.chance()),
+        1 => Value::Number(rng.below(100) as f64),
+        2 => Value::Number(-(rng.below(100) as f64) - 0.5),
+        _ => {
+            Value::String(
+                rng.pick(&["M", "John Doe", "%son", "a.b"]).to_string(),
+            )
+        }
+    }
+}
+
+fn condition(rng: &mut Rng) -> Expr {
+    let left = if rng.below(4) == 0 {
+        Expr::Function(Function {
+            name: rng.pick(&["min", "max", "lower"]).to_string(),
+            params: vec![column(rng)],
+        })
+    } else {
+        column(rng)
+    };
+    let (operator, right) = match rng.below(12) {
+        0 => (Operator::Is, Value::Null),
+        1 => (Operator::IsNot, Value::Null),
+        _ => {
+            let operator = rng
+                .pick(&[
+                    Operator::Eq,
+                    Operator::Neq,
+                    Operator::Lt,
+                    Operator::Lte,
+                    Operator::Gt,
+                    Operator::Gte,
+                    Operator::Like,
+                    Operator::Ilike,
+                    Operator::Starts,
+                    Operator::In,
+                    Operator::NotIn,
+                ])
+                .clone();
+            (operator, value(rng))
+        }
+    };
+    Expr::BinaryOperation(Box::new(BinaryOperation {
+        left,
+        operator,
+        right: Expr::Value(right),
+    }))
+}
+
+/// A chain of conditions, where a group is only generated where restq
+/// reads it back the same: as the first operand of the whole filter, or as
+/// the last operand of a chain. restq can't parse a group starting with a
+/// group, ie: `((a|b)&c)`, and `a|(b&c)&d` is parsed as `(a|(b&c))&d`.
+fn filter(rng: &mut Rng, depth: usize, first: bool) -> Expr {
+    if depth == 0 || rng.below(3) == 0 {
+        return condition(rng);
+    }
+    let left = if first && rng.chance() {
+        group(rng, depth - 1)
+    } else {
+        condition(rng)
+    };
+    let right = if rng.chance() {
+        group(rng, depth - 1)
+    } else {
+        filter(rng, depth - 1, false)
+    };
+    Expr::BinaryOperation(Box::new(BinaryOperation {
+        left,
+        operator: rng.pick(&[Operator::And, Operator::Or]).clone(),
+        right,
+    }))
+}
+
+/// a chain in a parenthesis, which is only needed around a connector
+fn group(rng: &mut Rng, depth: usize) -> Expr {
+    let expr = filter(rng, depth, false);
+    match &expr {
+        Expr::BinaryOperation(binop)
+            if binop.operator == Operator::And
+                || binop.operator == Operator::Or =>
+        {
+            Expr::Nested(Box::new(expr))
+        }
+        _ => expr,
+    }
+}
+
+fn select(rng: &mut Rng) -> Select {
+    let mut select = Select {
+        from_table: FromTable {
+            from: Table {
+                name: rng.pick(&["person", "bazaar.product"]).to_string(),
+            },
+            join: None,
+        },
+        ..Default::default()
+    };
+    if rng.chance() {
+        select.projection = Some(
+            (0..1 + rng.below(3))
+                .map(|i| {
+                    ExprRename {
+                        expr: column(rng),
+                        rename: if rng.chance() {
+                            Some(format!("alias{}", i))
+                        } else {
+                            None
+                        },
+                    }
+                })
+                .collect(),
+        );
+    }
+    if rng.below(4) != 0 {
+        select.filter = Some(filter(rng, 3, true));
+    }
+    if rng.below(4) == 0 {
+        select.group_by = Some(vec![column(rng)]);
+        if rng.chance() {
+            select.having = Some(condition(rng));
+        }
+    }
+    if rng.chance() {
+        select.order_by = Some(
+            (0..1 + rng.below(3))
+                .map(|_| {
+                    Order {
+                        expr: column(rng),
+                        direction: rng
+                            .pick(&[
+                                None,
+                                Some(Direction::Asc),
+                                Some(Direction::Desc),
+                            ])
+                            .clone(),
+                    }
+                })
+                .collect(),
+        );
+    }
+    select.range = match rng.below(4) {
+        0 => {
+            Some(Range::Page(Page {
+                page: 1 + rng.below(10) as i64,
+                page_size: 1 + rng.below(100) as i64,
+            }))
+        }
+        1 => {
+            Some(Range::Limit(Limit {
+                limit: 1 + rng.below(100) as i64,
+                offset: if rng.chance() {
+                    Some(rng.below(100) as i64)
+                } else {
+                    None
+                },
+            }))
+        }
+        _ => None,
+    };
+    select
+}
+
+#[test]
+fn generated_queries_round_trip() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..500 {
+        let query = select(&mut rng);
+        let displayed = format!("/{}", query);
+        let reparsed = inquerest::parse_query(&displayed).unwrap_or_else(|e| {
+            panic!("`{}` must be parsed: {}", displayed, e)
+        });
+        assert_eq!(reparsed, query, "`{}` must round trip", displayed);
+    }
+}
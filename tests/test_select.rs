@@ -0,0 +1,437 @@
+use inquerest::select::{
+    dedup_order_by,
+    eq_ignoring_range,
+};
+
+#[test]
+fn duplicate_order_by_column_is_removed() {
+    let url = "/person?age=lt.42&order_by=age.asc,height.desc,age.desc";
+    let mut query = inquerest::parse_query(url).expect("must be parsed");
+    let dropped = dedup_order_by(&mut query);
+
+    let expected = inquerest::parse_query(
+        "/person?age=lt.42&order_by=age.asc,height.desc",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+    assert_eq!(dropped.len(), 1);
+    assert_eq!(dropped[0].to_string(), "age.desc");
+}
+
+#[test]
+fn queries_differing_only_in_range_are_equal_ignoring_range() {
+    let first = inquerest::parse_query("/person?age=lt.42&limit=10&offset=0")
+        .expect("must be parsed");
+    let second = inquerest::parse_query("/person?age=lt.42&limit=10&offset=10")
+        .expect("must be parsed");
+    let third = inquerest::parse_query("/person?age=lt.13&limit=10&offset=10")
+        .expect("must be parsed");
+    assert_ne!(first, second);
+    assert!(eq_ignoring_range(&first, &second));
+    assert!(!eq_ignoring_range(&second, &third));
+}
+
+#[test]
+fn function_names_are_normalized_throughout_the_query() {
+    let mut query = inquerest::parse_query(
+        "/person?&group_by=SUM(age),grade&having=MIN(age)=gt.42&order_by=Max(height).desc",
+    )
+    .expect("must be parsed");
+    inquerest::select::normalize_function_names(&mut query);
+    let expected = inquerest::parse_query(
+        "/person?&group_by=sum(age),grade&having=min(age)=gt.42&order_by=max(height).desc",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn push_filter_appends_to_the_existing_filter() {
+    use inquerest::{
+        parse_filter,
+        select::push_filter,
+        BinaryOperation,
+        Expr,
+        Operator,
+    };
+
+    let url =
+        "/person?age=lt.42&(student=eq.true|gender=eq.'M')&order_by=age.desc";
+    let mut query = inquerest::parse_query(url).expect("must be parsed");
+    let original = query.filter.clone().expect("must have a filter");
+    let condition = parse_filter("grade=gt.5").unwrap();
+
+    push_filter(&mut query, condition.clone(), Operator::And).unwrap();
+    assert_eq!(
+        query.filter,
+        Some(Expr::BinaryOperation(Box::new(BinaryOperation {
+            left: original,
+            operator: Operator::And,
+            right: condition,
+        })))
+    );
+    assert_eq!(
+        query.to_string(),
+        "person?age=lt.42&(student=eq.true|gender=eq.'M')&grade=gt.5&order_by=age.desc"
+    );
+}
+
+#[test]
+fn push_filter_groups_the_other_connector() {
+    use inquerest::{
+        parse_filter,
+        select::push_filter,
+        Operator,
+    };
+
+    let mut query = inquerest::parse_query("/person?student=eq.true|age=lt.13")
+        .expect("must be parsed");
+    let condition = parse_filter("grade=gt.5").unwrap();
+    push_filter(&mut query, condition, Operator::And).unwrap();
+    assert_eq!(
+        query.to_string(),
+        "person?(student=eq.true|age=lt.13)&grade=gt.5"
+    );
+
+    // restq reads `a&b|c` as `a&(b|c)`, the `|` is not at the top level
+    let mut query =
+        inquerest::parse_query("/person?age=lt.13&student=eq.true|grade=gt.5")
+            .expect("must be parsed");
+    let condition = parse_filter("gender=eq.'M'").unwrap();
+    push_filter(&mut query, condition, Operator::And).unwrap();
+    assert_eq!(
+        query.to_string(),
+        "person?(age=lt.13&student=eq.true|grade=gt.5)&gender=eq.'M'"
+    );
+
+    let mut query = inquerest::parse_query("/person").unwrap();
+    let condition = parse_filter("grade=gt.5").unwrap();
+    push_filter(&mut query, condition.clone(), Operator::Or).unwrap();
+    assert_eq!(query.filter, Some(condition.clone()));
+
+    assert!(push_filter(&mut query, condition, Operator::Eq).is_err());
+}
+
+#[test]
+fn bind_values_are_in_sql_order() {
+    use inquerest::{
+        select::bind_values,
+        Value,
+    };
+
+    let url = "/person?age=lt.42&(student=eq.true|gender=eq.'M')&group_by=sum(age),grade,gender&having=min(age)=gt.18&order_by=age.desc";
+    let query = inquerest::parse_query(url).expect("must be parsed");
+    assert_eq!(
+        bind_values(&query),
+        vec![
+            &Value::Number(42.0),
+            &Value::Bool(true),
+            &Value::String("M".to_string()),
+            &Value::Number(18.0),
+        ]
+    );
+}
+
+#[test]
+fn map_values_uppercases_string_values() {
+    use inquerest::{
+        select::map_values,
+        Value,
+    };
+
+    let url = "/person?name=eq.'john'&(gender=eq.'m'|age=lt.13)&group_by=grade&having=min(nickname)=eq.'jo'";
+    let mut query = inquerest::parse_query(url).expect("must be parsed");
+    map_values(&mut query, |value| {
+        if let Value::String(s) = value {
+            *s = s.to_uppercase();
+        }
+    });
+    let expected = inquerest::parse_query(
+        "/person?name=eq.'JOHN'&(gender=eq.'M'|age=lt.13)&group_by=grade&having=min(nickname)=eq.'JO'",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn null_check_has_no_bind_value() {
+    use inquerest::{
+        select::bind_values,
+        Value,
+    };
+
+    let query = inquerest::parse_query("/person?deleted_at=is_not.null")
+        .expect("must be parsed");
+    assert!(bind_values(&query).is_empty());
+
+    let query =
+        inquerest::parse_query("/person?deleted_at=is_not.null&age=eq.5")
+            .expect("must be parsed");
+    assert_eq!(bind_values(&query), vec![&Value::Number(5.0)]);
+}
+
+#[test]
+fn range_conditions_on_the_same_column() {
+    use inquerest::{
+        select::range_conditions_for,
+        Value,
+    };
+    use std::ops::Bound;
+
+    let query =
+        inquerest::parse_query("/person?age=gt.18&student=eq.true&age=lte.65")
+            .expect("must be parsed");
+    assert_eq!(
+        range_conditions_for(&query, "age"),
+        Some((
+            Bound::Excluded(Value::Number(18.0)),
+            Bound::Included(Value::Number(65.0))
+        ))
+    );
+    assert_eq!(range_conditions_for(&query, "student"), None);
+
+    let query =
+        inquerest::parse_query("/person?13=lt.age").expect("must be parsed");
+    assert_eq!(
+        range_conditions_for(&query, "age"),
+        Some((Bound::Excluded(Value::Number(13.0)), Bound::Unbounded))
+    );
+}
+
+#[test]
+fn range_conditions_ignore_or() {
+    use inquerest::select::range_conditions_for;
+
+    let query = inquerest::parse_query("/person?age=gt.18|age=lt.65")
+        .expect("must be parsed");
+    assert_eq!(range_conditions_for(&query, "age"), None);
+}
+
+#[test]
+fn rename_table_throughout_the_query() {
+    use inquerest::select::rename_table;
+
+    let mut query = inquerest::parse_query(
+        "/person<-users{person.name,users.email}?person.age=lt.42&group_by=person.grade&order_by=person.age.desc",
+    )
+    .expect("must be parsed");
+    rename_table(&mut query, "person", "people");
+    let expected = inquerest::parse_query(
+        "/people<-users{people.name,users.email}?people.age=lt.42&group_by=people.grade&order_by=people.age.desc",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    let mut query = inquerest::parse_query("/users<-person?person.age=lt.42")
+        .expect("must be parsed");
+    rename_table(&mut query, "person", "people");
+    assert_eq!(query.to_string(), "users<-people?people.age=lt.42");
+}
+
+#[test]
+fn merging_filters_drops_the_shared_condition() {
+    use inquerest::select::merge_filter;
+
+    let mut query = inquerest::parse_query(
+        "/person?tenant_id=eq.5&(student=eq.true|gender=eq.'M')",
+    )
+    .expect("must be parsed");
+    let policy =
+        inquerest::parse_query("/person?age=lt.42&tenant_id=eq.5&age=lt.42")
+            .expect("must be parsed");
+    merge_filter(&mut query, &policy);
+    assert_eq!(
+        query.to_string(),
+        "person?tenant_id=eq.5&(student=eq.true|gender=eq.'M')&age=lt.42"
+    );
+}
+
+#[test]
+fn merging_into_a_nested_or_groups_it() {
+    use inquerest::select::merge_filter;
+
+    let mut query =
+        inquerest::parse_query("/person?age=lt.13&student=eq.true|grade=gt.5")
+            .expect("must be parsed");
+    let other = inquerest::parse_query("/person?tenant_id=eq.5").unwrap();
+    merge_filter(&mut query, &other);
+    assert_eq!(
+        query.to_string(),
+        "person?(age=lt.13&student=eq.true|grade=gt.5)&tenant_id=eq.5"
+    );
+}
+
+#[test]
+fn order_by_a_projection_alias() {
+    use inquerest::select::resolve_order_by_aliases;
+
+    let mut query = inquerest::parse_query(
+        "/person{sum(amount)=>total,grade}?&group_by=grade&order_by=total.desc,grade",
+    )
+    .expect("must be parsed");
+    resolve_order_by_aliases(&mut query);
+    let expected = inquerest::parse_query(
+        "/person{sum(amount)=>total,grade}?&group_by=grade&order_by=sum(amount).desc,grade",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+}
+
+#[test]
+fn order_by_a_select_alias_of_a_function() {
+    use inquerest::select::resolve_order_by_aliases;
+
+    let mut query = inquerest::parse_query(
+        "/person?select=sum(amount):total&group_by=name&order_by=total.desc",
+    )
+    .expect("must be parsed");
+    resolve_order_by_aliases(&mut query);
+    let expected = inquerest::parse_query(
+        "/person{sum(amount)=>total}?&group_by=name&order_by=sum(amount).desc",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    let query = inquerest::parse_query("/person?select=json_agg(orders):data")
+        .expect("must be parsed");
+    assert_eq!(
+        query,
+        inquerest::parse_query("/person{json_agg(orders)=>data}").unwrap()
+    );
+}
+
+#[test]
+fn policy_constrains_a_client_or() {
+    use inquerest::{
+        parse_filter,
+        select::rewrite_with_policy,
+    };
+
+    let policy = parse_filter("tenant_id=eq.5").unwrap();
+    let mut query =
+        inquerest::parse_query("/person?student=eq.true|tenant_id=eq.6")
+            .expect("must be parsed");
+    rewrite_with_policy(&mut query, &policy);
+    assert_eq!(
+        query.to_string(),
+        "person?tenant_id=eq.5&(student=eq.true|tenant_id=eq.6)"
+    );
+    let sql = query.into_sql_statement(None).unwrap().to_string();
+    assert!(
+        sql.contains(
+            "WHERE tenant_id = 5 AND (student = true OR tenant_id = 6)"
+        ),
+        "{}",
+        sql
+    );
+
+    // restq reads `a&b|c` as `a&(b|c)`, an OR which isn't at the top level
+    let mut query =
+        inquerest::parse_query("/person?a=eq.1&b=eq.2|c=eq.3").unwrap();
+    rewrite_with_policy(&mut query, &policy);
+    assert_eq!(
+        query.to_string(),
+        "person?tenant_id=eq.5&(a=eq.1&b=eq.2|c=eq.3)"
+    );
+    let sql = query.into_sql_statement(None).unwrap().to_string();
+    assert!(
+        sql.contains("WHERE tenant_id = 5 AND (a = 1 AND b = 2 OR c = 3)"),
+        "{}",
+        sql
+    );
+
+    let mut query = inquerest::parse_query("/person").unwrap();
+    rewrite_with_policy(&mut query, &policy);
+    assert_eq!(query.filter, Some(policy));
+}
+
+#[test]
+fn sql_skeleton_has_no_literal_values() {
+    use inquerest::select::sql_skeleton;
+
+    let url = "/person?age=lt.42&(student=eq.true|gender=eq.'M')&group_by=sum(age),grade,gender&having=min(age)=gt.42&order_by=age.desc,height.asc&page=20&page_size=100";
+    let query = inquerest::parse_query(url).expect("must be parsed");
+    let skeleton = sql_skeleton(&query).expect("must be converted");
+    assert_eq!(
+        skeleton,
+        "SELECT * FROM \"person\" WHERE \"age\" < ? AND (\"student\" = ? OR \"gender\" = ?) GROUP BY sum(\"age\"), \"grade\", \"gender\" HAVING min(\"age\") > ? ORDER BY \"age\" DESC, \"height\" ASC LIMIT ? OFFSET ?"
+    );
+    assert!(!skeleton.chars().any(|ch| ch.is_ascii_digit()));
+    assert!(!skeleton.contains("'M'"));
+
+    // the operators restq's sql conversion doesn't have are rendered too
+    let query = inquerest::parse_query(
+        "/person?name=ilike.'%john%'&deleted_at=is.null&city=starts.'New'&limit=10",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        sql_skeleton(&query).expect("must be converted"),
+        "SELECT * FROM \"person\" WHERE \"name\" ILIKE ? AND \"deleted_at\" IS NULL AND \"city\" ILIKE ? LIMIT ?"
+    );
+}
+
+#[test]
+fn having_refers_to_a_projection_position() {
+    use inquerest::select::resolve_having_ordinals;
+
+    let mut query = inquerest::parse_query(
+        "/person{grade,sum(amount)}?&group_by=grade&having=2=gt.100",
+    )
+    .expect("must be parsed");
+    resolve_having_ordinals(&mut query).expect("must be resolved");
+    let expected = inquerest::parse_query(
+        "/person{grade,sum(amount)}?&group_by=grade&having=sum(amount)=gt.100",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    let mut query = inquerest::parse_query(
+        "/person{grade,sum(amount)}?&group_by=grade&having=3=gt.100",
+    )
+    .expect("must be parsed");
+    assert!(resolve_having_ordinals(&mut query).is_err());
+}
+
+#[test]
+fn server_order_by_is_merged_after_the_client_one() {
+    use inquerest::select::merge_order_by;
+
+    let mut query =
+        inquerest::parse_query("/person?age=lt.42&order_by=name.desc,age.asc")
+            .expect("must be parsed");
+    let server = inquerest::parse_query("/person?&order_by=age.desc,id.asc")
+        .expect("must be parsed")
+        .order_by
+        .expect("must have an order_by");
+    merge_order_by(&mut query, server.clone());
+    let expected = inquerest::parse_query(
+        "/person?age=lt.42&order_by=name.desc,age.asc,id.asc",
+    )
+    .expect("must be parsed");
+    assert_eq!(query, expected);
+
+    let mut query =
+        inquerest::parse_query("/person?age=lt.42").expect("must be parsed");
+    merge_order_by(&mut query, server.clone());
+    assert_eq!(query.order_by, Some(server));
+}
+
+#[test]
+fn functions_used_in_every_clause_are_reported() {
+    use inquerest::select::referenced_functions;
+
+    let query = inquerest::parse_query(
+        "/person{grade,sum(age)}?lower(name)=eq.'j'&group_by=grade&having=min(age)=gt.18&order_by=sum(age).desc",
+    )
+    .expect("must be parsed");
+    assert_eq!(referenced_functions(&query), vec!["sum", "lower", "min"]);
+
+    let query =
+        inquerest::parse_query("/person?coalesce(upper(name),nick)=eq.x")
+            .expect("must be parsed");
+    assert_eq!(referenced_functions(&query), vec!["coalesce", "upper"]);
+
+    let query =
+        inquerest::parse_query("/person?age=lt.42").expect("must be parsed");
+    assert!(referenced_functions(&query).is_empty());
+}
@@ -0,0 +1,176 @@
+use inquerest::{
+    sql::{
+        render_column,
+        Dialect,
+    },
+    Column,
+};
+
+#[test]
+fn columns_are_quoted_for_the_dialect() {
+    let column = Column {
+        name: "person.age".to_string(),
+    };
+    assert_eq!(
+        render_column(&column, Dialect::Postgres),
+        r#""person"."age""#
+    );
+    assert_eq!(render_column(&column, Dialect::Mysql), "`person`.`age`");
+
+    assert_eq!(Dialect::Postgres.quote_identifier(r#"a"b"#), r#""a""b""#);
+    assert_eq!(Dialect::Mysql.quote_identifier("a`b"), "`a``b`");
+}
+
+#[test]
+fn query_to_sql() {
+    use inquerest::sql::to_sql;
+
+    let query = inquerest::parse_query(
+        "/person{name,sum(age)=>total}?age=lt.42&(student=eq.true|gender=eq.'M')&group_by=name&having=sum(age)=gt.42&order_by=name.desc&page=20&page_size=100",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        to_sql(&query, Dialect::Postgres).unwrap(),
+        r#"SELECT "name", sum("age") AS "total" FROM "person" WHERE "age" < 42 AND ("student" = TRUE OR "gender" = 'M') GROUP BY "name" HAVING sum("age") > 42 ORDER BY "name" DESC LIMIT 100 OFFSET 1900"#
+    );
+}
+
+#[test]
+fn count_star_is_the_wildcard() {
+    use inquerest::{
+        expr::is_wildcard,
+        sql::to_sql,
+        Expr,
+        Function,
+        Value,
+    };
+
+    let query = inquerest::parse_query(
+        "/person{gender,count(*)=>total}?&group_by=gender&having=count(*)=gt.1",
+    )
+    .expect("must be parsed");
+    let projection = query.projection.as_ref().expect("must have a projection");
+    assert_eq!(
+        projection[1].expr,
+        Expr::Function(Function {
+            name: "count".into(),
+            params: vec![Expr::Value(Value::String("*".into()))],
+        })
+    );
+    if let Expr::Function(function) = &projection[1].expr {
+        assert!(is_wildcard(&function.params[0]));
+    }
+    assert_eq!(
+        to_sql(&query, Dialect::Postgres).unwrap(),
+        r#"SELECT "gender", count(*) AS "total" FROM "person" GROUP BY "gender" HAVING count(*) > 1"#
+    );
+}
+
+#[test]
+fn the_parsed_grouping_is_kept() {
+    use inquerest::sql::to_sql;
+
+    // restq groups `a&b|c` as `a&(b|c)`
+    let query = inquerest::parse_query(
+        "/person?age=lt.13&student=eq.true|gender=eq.'M'",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        to_sql(&query, Dialect::Mysql).unwrap(),
+        "SELECT * FROM `person` WHERE `age` < 13 AND (`student` = TRUE OR `gender` = 'M')"
+    );
+
+    let query = inquerest::parse_query(
+        "/person?(age=lt.13&student=eq.true)|gender=eq.'M'",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        to_sql(&query, Dialect::Mysql).unwrap(),
+        "SELECT * FROM `person` WHERE (`age` < 13 AND `student` = TRUE) OR `gender` = 'M'"
+    );
+}
+
+#[test]
+fn every_operator_is_rendered() {
+    use inquerest::sql::to_sql;
+
+    let query = inquerest::parse_query(
+        r"/person?name=starts.'Jo'&nickname=ilike.'%o\'s'&status=in.active&deleted_at=is.null&grade=not_in.5&age=lte.-1.5&limit=10",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        to_sql(&query, Dialect::Postgres).unwrap(),
+        r#"SELECT * FROM "person" WHERE "name" ILIKE 'Jo%' AND "nickname" ILIKE '%o''s' AND "status" IN ("active") AND "deleted_at" IS NULL AND "grade" NOT IN (5) AND "age" <= -1.5 LIMIT 10"#
+    );
+    assert!(to_sql(&query, Dialect::Mysql).is_err());
+}
+
+#[test]
+fn filter_to_where_clause() {
+    use inquerest::{
+        parse_filter,
+        sql::to_where_clause,
+    };
+
+    let where_clause = |filter: &str| {
+        to_where_clause(&parse_filter(filter).unwrap(), Dialect::Postgres)
+            .unwrap()
+    };
+    assert_eq!(
+        where_clause("a=lt.13&b=eq.true|c=eq.'M'"),
+        r#""a" < 13 AND ("b" = TRUE OR "c" = 'M')"#
+    );
+    assert_eq!(
+        where_clause("(a=lt.13&b=eq.true)|c=eq.'M'"),
+        r#"("a" < 13 AND "b" = TRUE) OR "c" = 'M'"#
+    );
+    assert_eq!(
+        where_clause("a=lt.13|b=eq.true&c=eq.'M'"),
+        r#""a" < 13 OR ("b" = TRUE AND "c" = 'M')"#
+    );
+    assert_eq!(
+        where_clause("a=lt.13&b=eq.true&c=eq.'M'"),
+        r#""a" < 13 AND "b" = TRUE AND "c" = 'M'"#
+    );
+}
+
+#[test]
+fn sql_text_of_operators_and_directions() {
+    use inquerest::{
+        sql::{
+            sql_direction,
+            sql_operator,
+        },
+        Direction,
+        Operator,
+    };
+
+    assert_eq!(sql_operator(&Operator::Gte), ">=");
+    assert_eq!(sql_operator(&Operator::Neq), "!=");
+    assert_eq!(sql_operator(&Operator::NotIn), "NOT IN");
+    assert_eq!(sql_operator(&Operator::Ilike), "ILIKE");
+    assert_eq!(sql_operator(&Operator::Starts), "ILIKE");
+    assert_eq!(sql_operator(&Operator::Or), "OR");
+    assert_eq!(sql_direction(&Direction::Desc), "DESC");
+}
+
+#[test]
+fn page_before_the_first_or_overflowing_is_an_error() {
+    use inquerest::{
+        parse_query,
+        sql::to_sql,
+    };
+
+    let query = parse_query("/person?page=0&page_size=20").unwrap();
+    assert!(to_sql(&query, Dialect::Postgres).is_err());
+
+    let url = format!("/person?page={}&page_size=20", i64::MAX);
+    let query = parse_query(&url).unwrap();
+    assert!(to_sql(&query, Dialect::Postgres).is_err());
+
+    let query = parse_query("/person?page=1&page_size=20").unwrap();
+    assert_eq!(
+        to_sql(&query, Dialect::Postgres).unwrap(),
+        r#"SELECT * FROM "person" LIMIT 20 OFFSET 0"#
+    );
+}
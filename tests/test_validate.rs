@@ -0,0 +1,230 @@
+use inquerest::{
+    parse_query,
+    validate::*,
+};
+
+#[test]
+fn group_by_with_all_columns_is_invalid() {
+    let query = parse_query("/person?&group_by=grade").expect("must be parsed");
+    assert_eq!(
+        validate_group_by(&query),
+        Err(ValidationError::WildcardWithGroupBy)
+    );
+
+    let query =
+        parse_query("/person{grade}&group_by=grade").expect("must be parsed");
+    assert_eq!(validate_group_by(&query), Ok(()));
+}
+
+#[test]
+fn query_exceeding_the_condition_limit() {
+    let limits = AstLimits {
+        max_conditions: 3,
+        ..Default::default()
+    };
+    let query = parse_query(
+        "/person?age=lt.42&(student=eq.true|gender=eq.'M')&group_by=grade&having=min(age)=gt.18",
+    )
+    .expect("must be parsed");
+    assert_eq!(
+        enforce_limits(&query, &limits),
+        Err(ValidationError::TooManyConditions { count: 4, max: 3 })
+    );
+
+    let query = parse_query(
+        "/person?age=lt.42&(student=eq.true|gender=eq.'M')&limit=10",
+    )
+    .expect("must be parsed");
+    assert_eq!(enforce_limits(&query, &limits), Ok(()));
+}
+
+#[test]
+fn query_exceeding_the_join_and_column_limits() {
+    let limits = AstLimits {
+        max_joins: 1,
+        max_columns: 2,
+        ..Default::default()
+    };
+    let query = parse_query("/person<-users<-orders").expect("must be parsed");
+    assert_eq!(
+        enforce_limits(&query, &limits),
+        Err(ValidationError::TooManyJoins { count: 2, max: 1 })
+    );
+
+    let query = parse_query("/person{name,age,grade}").expect("must be parsed");
+    assert_eq!(
+        enforce_limits(&query, &limits),
+        Err(ValidationError::TooManyColumns { count: 3, max: 2 })
+    );
+}
+
+#[test]
+fn query_exceeding_the_page_size_limit() {
+    let limits = AstLimits {
+        max_page_size: 1000,
+        ..Default::default()
+    };
+    let query =
+        parse_query("/person?age=lt.42&limit=5000").expect("must be parsed");
+    let error = enforce_limits(&query, &limits).unwrap_err();
+    assert_eq!(
+        error,
+        ValidationError::PageSizeExceeded {
+            requested: 5000,
+            max: 1000
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "The query requests 5000 rows, the maximum allowed is 1000"
+    );
+
+    let query =
+        parse_query("/person?page=2&page_size=1001").expect("must be parsed");
+    assert!(enforce_limits(&query, &limits).is_err());
+    let query =
+        parse_query("/person?limit=1000&offset=5000").expect("must be parsed");
+    assert_eq!(enforce_limits(&query, &limits), Ok(()));
+}
+
+#[test]
+fn query_without_a_limit_is_unbounded() {
+    let query = parse_query("/person?age=lt.42").expect("must be parsed");
+    let error = enforce_limits(&query, &AstLimits::default()).unwrap_err();
+    assert_eq!(error, ValidationError::Unbounded { max: 1000 });
+    assert_eq!(
+        error.to_string(),
+        "The query has no limit, the maximum allowed is 1000 rows"
+    );
+}
+
+#[test]
+fn in_with_a_single_value_is_invalid() {
+    use inquerest::{
+        expr::requires_list,
+        Operator,
+    };
+
+    assert!(requires_list(&Operator::In));
+    assert!(requires_list(&Operator::NotIn));
+    assert!(!requires_list(&Operator::Eq));
+
+    let query = parse_query("/person?age=lt.42&status=in.active")
+        .expect("must be parsed");
+    assert_eq!(
+        validate_list_operands(&query),
+        Err(ValidationError::ScalarForList {
+            operator: "in".to_string(),
+            right: "active".to_string(),
+        })
+    );
+
+    let query = parse_query("/person?age=lt.42").expect("must be parsed");
+    assert_eq!(validate_list_operands(&query), Ok(()));
+}
+
+#[test]
+fn ilike_is_not_supported_in_mysql() {
+    use inquerest::sql::Dialect;
+
+    let query = parse_query("/person?age=lt.42&name=ilike.'%son'")
+        .expect("must be parsed");
+    assert_eq!(
+        validate_operators_supported(&query, Dialect::Mysql),
+        Err(ValidationError::UnsupportedOperator {
+            operator: "ilike".to_string(),
+            dialect: Dialect::Mysql,
+        })
+    );
+    assert_eq!(
+        validate_operators_supported(&query, Dialect::Postgres),
+        Ok(())
+    );
+
+    let query = parse_query("/person?age=lt.42&name=like.'%son'")
+        .expect("must be parsed");
+    assert_eq!(validate_operators_supported(&query, Dialect::Mysql), Ok(()));
+}
+
+#[test]
+fn non_finite_numbers_are_invalid() {
+    use inquerest::{
+        expr::map_values,
+        Value,
+    };
+
+    let query =
+        parse_query("/person?age=lt.42&height=gt.1.5").expect("must be parsed");
+    assert_eq!(validate_finite_numbers(&query), Ok(()));
+
+    for number in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let mut query = query.clone();
+        map_values(query.filter.as_mut().unwrap(), &mut |value| {
+            if *value == Value::Number(42.0) {
+                *value = Value::Number(*number);
+            }
+        });
+        assert_eq!(
+            validate_finite_numbers(&query),
+            Err(ValidationError::NonFiniteNumber {
+                value: number.to_string()
+            })
+        );
+    }
+}
+
+#[test]
+fn only_the_allowed_columns_can_be_used() {
+    let allowed = ["name", "age", "grade"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+
+    let query = parse_query(
+        "/person{grade,max(age)=>oldest}?name=starts.'J'&group_by=grade&having=max(age)=gt.18&order_by=oldest.desc",
+    )
+    .expect("must be parsed");
+    assert_eq!(validate_columns_allowed(&query, &allowed), Ok(()));
+
+    let query = parse_query("/person{name}?age=lt.42&salary=gt.1000")
+        .expect("must be parsed");
+    assert_eq!(
+        validate_columns_allowed(&query, &allowed),
+        Err(ValidationError::ColumnNotAllowed {
+            column: "salary".to_string()
+        })
+    );
+
+    let query = parse_query("/person{name,password}").expect("must be parsed");
+    assert_eq!(
+        validate_columns_allowed(&query, &allowed),
+        Err(ValidationError::ColumnNotAllowed {
+            column: "password".to_string()
+        })
+    );
+}
+
+#[test]
+fn a_rename_does_not_allow_the_column_of_its_name() {
+    let allowed = ["name"].iter().map(|column| column.to_string()).collect();
+    for url in &[
+        "/person{name=>password}?password=eq.'x'",
+        "/person?select=name,password:name&password=eq.'x'",
+        "/person{name=>password}?&group_by=password",
+        "/person{name=>password}?&order_by=lower(password)",
+    ] {
+        let query = parse_query(url).expect("must be parsed");
+        assert_eq!(
+            validate_columns_allowed(&query, &allowed),
+            Err(ValidationError::ColumnNotAllowed {
+                column: "password".to_string()
+            }),
+            "{}",
+            url
+        );
+    }
+
+    let query = parse_query("/person{name=>password}?&order_by=password.desc")
+        .expect("must be parsed");
+    assert_eq!(validate_columns_allowed(&query, &allowed), Ok(()));
+}
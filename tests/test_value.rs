@@ -0,0 +1,201 @@
+use inquerest::{
+    parse_filter,
+    parse_query,
+    value::*,
+    Expr,
+    Value,
+};
+
+/// the value on the right side of a single condition
+fn value_of(filter: &str) -> Value {
+    match parse_filter(filter).expect("must be parsed") {
+        Expr::BinaryOperation(binop) => {
+            match binop.right {
+                Expr::Value(value) => value,
+                right => panic!("expecting a value, got {:?}", right),
+            }
+        }
+        expr => panic!("expecting a condition, got {:?}", expr),
+    }
+}
+
+#[test]
+fn unquoted_dates_are_date_values() {
+    let value = value_of("created_at=gte.2023-01-01");
+    assert_eq!(value, Value::String("2023-01-01".to_string()));
+    assert_eq!(as_date(&value).unwrap().to_string(), "2023-01-01");
+    assert_eq!(as_datetime(&value), None);
+
+    let query =
+        parse_query("/orders?created_at=gte.2023-01-01&total=gt.100").unwrap();
+    assert_eq!(
+        query.to_string(),
+        "orders?created_at=gte.'2023-01-01'&total=gt.100"
+    );
+}
+
+#[test]
+fn rfc3339_timestamps_are_datetime_values() {
+    let value = value_of("created_at=lt.2023-01-01T10:30:00+02:00");
+    let datetime = as_datetime(&value).expect("must be a timestamp");
+    assert_eq!(datetime.to_rfc3339(), "2023-01-01T10:30:00+02:00");
+    assert_eq!(as_date(&value), None);
+
+    let value = value_of("created_at=lt.'2023-01-01T10:30:00Z'");
+    assert!(as_datetime(&value).is_some());
+}
+
+#[test]
+fn other_strings_are_not_dates() {
+    for v in &["2023-02-31", "2023-1-1", "january", "2023-01-01T25:00:00Z"] {
+        let value = Value::String(v.to_string());
+        assert_eq!(as_date(&value), None, "{}", v);
+        assert_eq!(as_datetime(&value), None, "{}", v);
+    }
+    assert_eq!(
+        value_of("name=eq.'january'"),
+        Value::String("january".into())
+    );
+    assert_eq!(as_date(&Value::Number(2023.0)), None);
+}
+
+#[test]
+fn unquoted_uuids_are_uuid_values() {
+    let id = "550e8400-e29b-41d4-a716-446655440000";
+    let value = value_of(&format!("id=eq.{}", id));
+    assert_eq!(value, Value::String(id.to_string()));
+    assert_eq!(as_uuid(&value).unwrap().to_string(), id);
+
+    let query = parse_query(&format!("/person?id=eq.{}", id)).unwrap();
+    let reparsed = parse_query(&format!("/{}", query)).unwrap();
+    assert_eq!(reparsed, query);
+    assert_eq!(query.to_string(), format!("person?id=eq.'{}'", id));
+}
+
+#[test]
+fn malformed_uuids_are_not_uuid_values() {
+    for v in &[
+        "550e8400e29b41d4a716446655440000",
+        "550e8400-e29b-41d4-a716-44665544000",
+        "550e8400-e29b-41d4-a716-44665544000g",
+        "550e840-0e29b-41d4-a716-446655440000",
+        "deadbeef",
+    ] {
+        assert_eq!(as_uuid(&Value::String(v.to_string())), None, "{}", v);
+    }
+    // an unquoted malformed uuid isn't quoted, it is read as it was before
+    let malformed = "550e8400-e29b-41d4-a716-44665544000g";
+    let quoted = parse_filter(&format!("id=eq.'{}'", malformed)).unwrap();
+    assert_ne!(
+        parse_filter(&format!("id=eq.{}", malformed)).ok(),
+        Some(quoted)
+    );
+}
+
+#[test]
+fn array_literals_are_arrays() {
+    let value = value_of(r#"tags=eq.{red,"dark green",blue}"#);
+    assert_eq!(
+        as_array(&value).unwrap(),
+        vec![
+            Element::Value(Value::String("red".into())),
+            Element::Value(Value::String("dark green".into())),
+            Element::Value(Value::String("blue".into())),
+        ]
+    );
+
+    let value = value_of("scores=eq.[1,2.5,[3,4]]");
+    assert_eq!(
+        as_array(&value).unwrap(),
+        vec![
+            Element::Value(Value::Number(1.0)),
+            Element::Value(Value::Number(2.5)),
+            Element::Array(vec![
+                Element::Value(Value::Number(3.0)),
+                Element::Value(Value::Number(4.0)),
+            ]),
+        ]
+    );
+
+    assert_eq!(as_array(&value_of("tags=eq.{}")).unwrap(), vec![]);
+
+    for v in &["{a,b", "{a,b]", "[1,{2]}", "red", "{a,}"] {
+        assert_eq!(as_array(&Value::String(v.to_string())), None, "{}", v);
+    }
+}
+
+#[test]
+fn iso_durations_are_interval_values() {
+    let value = value_of("duration=gt.PT1H30M");
+    assert_eq!(value, Value::String("PT1H30M".to_string()));
+    assert_eq!(
+        as_interval(&value),
+        Some(Interval {
+            months: 0,
+            days: 0,
+            microseconds: 5_400_000_000,
+        })
+    );
+    assert_eq!(
+        as_interval(&Value::String("P1Y2M1W3DT0.5S".to_string())),
+        Some(Interval {
+            months: 14,
+            days: 10,
+            microseconds: 500_000,
+        })
+    );
+    assert_eq!(
+        parse_query("/tasks?duration=gt.PT1H30M")
+            .unwrap()
+            .to_string(),
+        "tasks?duration=gt.'PT1H30M'"
+    );
+}
+
+#[test]
+fn postgres_intervals_are_interval_values() {
+    let value = value_of("age=gt.'1 day'");
+    assert_eq!(
+        as_interval(&value),
+        Some(Interval {
+            months: 0,
+            days: 1,
+            microseconds: 0,
+        })
+    );
+    assert_eq!(
+        as_interval(&Value::String("2 years 1 mon 2 hours 30 mins".into())),
+        Some(Interval {
+            months: 25,
+            days: 0,
+            microseconds: 9_000_000_000,
+        })
+    );
+}
+
+#[test]
+fn malformed_intervals_are_not_interval_values() {
+    for v in &[
+        "P",
+        "PT",
+        "P1H",
+        "PT1D",
+        "P1D1Y",
+        "P1.5D",
+        "PT1e5H",
+        "day",
+        "1 days 2",
+        "1 fortnight",
+        "1.5 days",
+        "PT-inf S",
+        "99999999999 days",
+    ] {
+        assert_eq!(as_interval(&Value::String(v.to_string())), None, "{}", v);
+    }
+    assert_eq!(as_interval(&Value::Number(1.0)), None);
+    // an unquoted malformed duration isn't quoted, it is read as it was before
+    assert!(matches!(
+        parse_filter("duration=gt.P1H").unwrap(),
+        Expr::BinaryOperation(binop) if matches!(binop.right, Expr::Column(_))
+    ));
+}